@@ -1,31 +1,72 @@
-use crate::game::{simulate_game, Dice, ShutTheBox, Statistics};
-use std::sync::atomic::{AtomicBool, Ordering};
+use rand::Rng;
+use shutthebox::dice::{Dice, DiceConfig};
+use shutthebox::error::GameError;
+use shutthebox::exact::Outcomes;
+use shutthebox::game::{game_seed, simulate_game, simulate_seeded, ShutTheBox, Statistics};
+use shutthebox::hotseat::{HotSeat, MAX_PLAYERS, MIN_PLAYERS};
+use shutthebox::replay::Replay;
+use shutthebox::review::{review, MoveReview};
+use shutthebox::rules::{OneDie, Rules};
+use shutthebox::score::ScoreRule;
+use shutthebox::solver::{Objective, Solver, MAX_SOLVER_TILES};
+use shutthebox::strategy::{Strategy, StrategyKind};
+use shutthebox::tiles::TileSet;
+use shutthebox::tournament::{lineup, Tournament};
+use std::cmp;
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tui::widgets::ListState;
 extern crate num_cpus;
 
-const IDLE_TASKS: [&str; 5] = [
+const IDLE_TASKS: [&str; 7] = [
     "Play Manually!",
+    "Play Hot-Seat!",
     "Autoplay: 1x",
     "Autoplay: 10x",
     "Autoplay: Ludicrous",
     "Autoplay: Plaid",
+    "Compare Strategies",
 ];
 
 const MANUAL_TASKS: [&str; 2] = ["Lock Selection", "Return"];
-const LOST_TASKS: [&str; 2] = ["YOU LOST -- Retry?", "Return"];
-const WON_TASKS: [&str; 2] = ["YOU WON -- Play Again?", "Return"];
+const DICE_TASKS: [&str; 3] = ["Roll One Die", "Roll All Dice", "Return"];
+const LOST_TASKS: [&str; 3] = ["YOU LOST -- Retry?", "Review Moves", "Return"];
+const WON_TASKS: [&str; 3] = ["YOU WON -- Play Again?", "Review Moves", "Return"];
 const AUTO_TASKS: [&str; 1] = ["Return"];
+const TURN_TASKS: [&str; 2] = ["Next Player", "Return"];
+const ROUND_TASKS: [&str; 2] = ["Next Round", "Return"];
+const REVIEW_TASKS: [&str; 1] = ["Done Reviewing"];
+const REPLAY_TASKS: [&str; 1] = ["Return"];
+
+/// Board sizes cycled through from the main menu
+const BOARD_SIZES: [usize; 3] = [9, 10, 12];
+
+/// Limits on the tiles shut per roll cycled through from the main menu
+const MAX_SHUT_CHOICES: [Option<usize>; 3] = [None, Some(2), Some(1)];
+
+/// Fair dice (count, sides) cycled through from the main menu
+const DICE_CHOICES: [(usize, usize); 4] = [(2, 6), (3, 6), (1, 8), (2, 8)];
+
+/// Games every strategy plays per tick when comparing strategies
+const COMPARE_GAMES: u64 = 2000;
+
+/// How often simulation workers publish their local statistics
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(50);
 
 #[derive(PartialEq)]
 pub enum AppState {
     Idle,
     ManualGame,
+    Review,
+    Replay,
     Auto1x,
     Auto10x,
     AutoFast,
     AutoPlaid,
+    Compare,
 }
 
 // pub mod AppState {
@@ -52,6 +93,14 @@ pub enum AppState {
 //     }
 // }
 
+/// A locked manual move, kept so it can be undone and redone
+struct ManualMove {
+    before: Vec<usize>,        // Dice the move was played on
+    tiles: Vec<usize>,         // Tiles shut by the move
+    rolls: usize,              // Rolls in the game when the move was locked
+    after: Option<Vec<usize>>, // Dice rolled after the move, filled in when undone
+}
+
 pub struct App<'a> {
     pub title: &'a str,
     pub should_quit: bool,
@@ -64,32 +113,127 @@ pub struct App<'a> {
     pub staging: Vec<usize>,
     pub gameover: bool,
     pub plotidx: usize,
+    pub choosing_dice: bool,
+    pub rules: Rules,
+    pub strategy: StrategyKind,
+    pub score_rule: ScoreRule,
+    pub players: Vec<String>,
+    pub hotseat: Option<HotSeat>,
+    pub optimal: Option<f64>,
+    pub exact: Option<Outcomes>,
+    pub hint: Option<Objective>,
+    pub review: Vec<MoveReview>,
+    pub review_idx: usize,
+    pub replay: Option<Replay>,
+    pub replay_step: usize,
+    pub status: Option<String>,
+    /// Why the last manual or bot move was refused
+    pub rejected: Option<GameError>,
+    pub tournament: Option<Tournament>,
+    pub seed: Option<u64>,
+    bot: Box<dyn Strategy>,
+    /// Seed of the dice for the game being played
+    game_seed: u64,
+    /// Index of the next game, to derive its seed from `seed`
+    next_game: u64,
+    /// Last finished Auto1x game and the seed it was played with
+    last_game: Option<(ShutTheBox, u64)>,
+    hint_solver: Option<Solver>,
+    undo: Vec<ManualMove>,
+    redo: Vec<ManualMove>,
     thread_handles: Vec<JoinHandle<()>>,
     thread_cancel: Arc<AtomicBool>,
 }
 
-fn run_simulations(statsmutex: Arc<Mutex<Statistics>>, cancel_flag: Arc<AtomicBool>) {
+fn run_simulations(
+    statsmutex: Arc<Mutex<Statistics>>,
+    cancel_flag: Arc<AtomicBool>,
+    strategy: StrategyKind,
+    rules: Rules,
+    seed: Option<u64>,
+    counter: Arc<AtomicU64>,
+) {
+    let mut strategy = strategy.build(&rules);
+    let mut dice = Dice::new().with_config(&rules.dice);
+    let mut local = Statistics::for_rules(&rules);
+    let mut last_publish = Instant::now();
     while !cancel_flag.load(Ordering::SeqCst) {
-        let game = simulate_game();
-        let mut stats = statsmutex.lock().unwrap();
-        stats.save_game(&game);
+        // Seeded runs share a game counter so every game index is played exactly once
+        let game = match seed {
+            Some(seed) => {
+                let index = counter.fetch_add(1, Ordering::SeqCst);
+                simulate_seeded(strategy.as_mut(), game_seed(seed, index), &rules)
+            }
+            None => simulate_game(strategy.as_mut(), &mut dice, &rules),
+        };
+        local.save_game(&game);
+        // Only touch the shared statistics occasionally to avoid lock contention,
+        // and between golf rounds so they are not split
+        if last_publish.elapsed() >= PUBLISH_INTERVAL && local.golf_card.games == 0 {
+            statsmutex.lock().unwrap().merge(&local);
+            local = Statistics::for_rules(&rules);
+            last_publish = Instant::now();
+        }
+    }
+    statsmutex.lock().unwrap().merge(&local);
+}
+
+/// Optimal win probability under the rules, if the board is small enough to solve
+fn optimal_win_rate(rules: &Rules) -> Option<f64> {
+    if rules.tiles > MAX_SOLVER_TILES {
+        return None;
     }
+    let game = ShutTheBox::with_rules(rules);
+    Some(Solver::new(rules, Objective::WinProbability).game_value(&game))
+}
+
+/// Exact outcomes of the strategy under the rules, if it is deterministic and the board solvable
+fn exact_outcomes(strategy: StrategyKind, rules: &Rules) -> Option<Outcomes> {
+    if !strategy.deterministic() || rules.tiles > MAX_SOLVER_TILES {
+        return None;
+    }
+    Some(Outcomes::new(rules, strategy.build(rules).as_mut()))
 }
 
 impl<'a> App<'a> {
     pub fn new(title: &'a str) -> App<'a> {
+        let rules = Rules::default();
         App {
             title,
             should_quit: false,
             state: AppState::Idle,
             tasks: StatefulList::with_items(IDLE_TASKS.to_vec()),
-            game: ShutTheBox::init(12),
+            game: ShutTheBox::with_rules(&rules),
             selection: 0,
             dice: Dice::new(),
             staging: Vec::with_capacity(5),
             gameover: false,
             plotidx: 0,
-            stats: Arc::new(Mutex::new(Statistics::new())),
+            choosing_dice: false,
+            rules: rules.clone(),
+            strategy: StrategyKind::Greedy,
+            score_rule: ScoreRule::Sum,
+            players: vec!["Player 1".to_string(), "Player 2".to_string()],
+            hotseat: None,
+            optimal: optimal_win_rate(&rules),
+            exact: exact_outcomes(StrategyKind::Greedy, &rules),
+            seed: None,
+            bot: StrategyKind::Greedy.build(&rules),
+            hint: None,
+            hint_solver: None,
+            review: Vec::new(),
+            review_idx: 0,
+            replay: None,
+            replay_step: 0,
+            status: None,
+            rejected: None,
+            tournament: None,
+            game_seed: 0,
+            next_game: 0,
+            last_game: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            stats: Arc::new(Mutex::new(Statistics::for_rules(&rules))),
             thread_handles: vec![],
             thread_cancel: Arc::new(AtomicBool::new(false)),
         }
@@ -99,10 +243,8 @@ impl<'a> App<'a> {
         match self.state {
             AppState::Idle => self.tasks.previous(),
             AppState::ManualGame => match self.tasks.state.selected() {
-                Some(0) => {
-                    if !self.game.victory() {
-                        self.tasks.state.select(None);
-                    }
+                Some(0) if !self.game.victory() && !self.choosing_dice => {
+                    self.tasks.state.select(None);
                 }
                 Some(_) => {
                     self.tasks.previous();
@@ -119,7 +261,7 @@ impl<'a> App<'a> {
             AppState::ManualGame => match self.tasks.state.selected() {
                 Some(idx) => {
                     if idx == self.tasks.items.len() - 1 {
-                        if !self.game.victory() {
+                        if !self.game.victory() && !self.choosing_dice {
                             self.tasks.state.select(None);
                         }
                     } else {
@@ -135,26 +277,118 @@ impl<'a> App<'a> {
     }
 
     fn return_to_menu(&mut self) {
+        self.manual_record();
         self.gameover = false;
+        self.choosing_dice = false;
         self.state = AppState::Idle;
         self.tasks = StatefulList::with_items(IDLE_TASKS.to_vec());
         self.tasks.state.select(Some(0));
-        self.game = ShutTheBox::init(12);
+        self.game = ShutTheBox::with_rules(&self.rules);
+        self.hotseat = None;
+        self.replay = None;
+        self.last_game = None;
+        self.tournament = None;
+        self.rejected = None;
+        self.staging.clear();
+    }
+
+    /// Switch to new rules, clearing statistics if any rule changed
+    pub fn set_rules(&mut self, rules: Rules) {
+        let dice_changed = rules.dice != self.rules.dice;
+        if rules.tiles != self.rules.tiles {
+            self.selection = 0;
+        }
+        if rules != self.rules {
+            self.stats = Arc::new(Mutex::new(Statistics::for_rules(&rules)));
+        }
+        self.game = ShutTheBox::with_rules(&rules);
+        self.rules = rules;
+        self.optimal = optimal_win_rate(&self.rules);
+        self.hint_solver = None;
+        if !self.strategy.supports(self.rules.tiles) {
+            self.strategy = StrategyKind::Greedy;
+        }
+        self.bot = self.strategy.build(&self.rules);
+        self.exact = exact_outcomes(self.strategy, &self.rules);
+        if dice_changed {
+            self.reset_dice();
+        }
+    }
+
+    /// Start the seeded sequence of games over
+    fn reset_dice(&mut self) {
+        self.next_game = 0;
+        self.dice = Dice::new().with_config(&self.rules.dice);
+    }
+
+    /// Seed the dice and bot for a new game, like `simulate_seeded`, so a replay can reproduce it
+    fn seed_game(&mut self) {
+        let seed = match self.seed {
+            Some(seed) => game_seed(seed, self.next_game),
+            None => rand::thread_rng().gen(),
+        };
+        self.next_game += 1;
+        self.game_seed = seed;
+        self.bot.reseed(!seed);
+        self.dice = Dice::with_seed(seed).with_config(&self.rules.dice);
+    }
+
+    /// Roll for the bot's next turn, letting it pick how many dice
+    fn bot_roll(&mut self) {
+        self.rules
+            .roll(&mut self.dice, self.game.open_tiles(), self.bot.as_mut());
+    }
+
+    fn spawn_workers(&mut self, count: usize) {
+        self.thread_cancel.store(false, Ordering::SeqCst);
+        let counter = Arc::new(AtomicU64::new(0));
+        for _ in 0..count {
+            let worker_cancel_flag = self.thread_cancel.clone();
+            let worker_stats = self.stats.clone();
+            let worker_strategy = self.strategy;
+            let worker_rules = self.rules.clone();
+            let worker_seed = self.seed;
+            let worker_counter = counter.clone();
+            self.thread_handles.push(spawn(move || {
+                run_simulations(
+                    worker_stats,
+                    worker_cancel_flag,
+                    worker_strategy,
+                    worker_rules,
+                    worker_seed,
+                    worker_counter,
+                )
+            }));
+        }
     }
 
     fn manual_end_game(&mut self, result: bool) {
-        let items = if result { WON_TASKS } else { LOST_TASKS };
+        let items = match &mut self.hotseat {
+            Some(hotseat) => match hotseat.end_turn(&self.game) {
+                Some(_) => ROUND_TASKS.to_vec(),
+                None => TURN_TASKS.to_vec(),
+            },
+            None if result => WON_TASKS.to_vec(),
+            None => LOST_TASKS.to_vec(),
+        };
         self.gameover = true;
-        self.tasks = StatefulList::with_items(items.to_vec());
+        self.tasks = StatefulList::with_items(items);
         self.tasks.state.select(Some(0));
-        self.stats.lock().unwrap().save_game(&self.game);
+    }
+
+    /// Save a finished manual game once the player moves on, since it can still be undone
+    fn manual_record(&mut self) {
+        if self.state == AppState::ManualGame && self.gameover {
+            self.stats.lock().unwrap().save_game(&self.game);
+        }
     }
 
     fn manual_reroll(&mut self) {
         self.tasks.state.select(None);
         self.staging.clear();
+        self.rejected = None;
         let open = self.game.get_open();
-        if open.len() == 0 {
+        if open.is_empty() {
             // CONGRATULATIONS! You win!
             self.manual_end_game(true);
             return;
@@ -162,13 +396,31 @@ impl<'a> App<'a> {
         if open.len() < self.game.total {
             self.selection = *open
                 .iter()
-                .skip_while(|&x| *x <= self.selection)
-                .next()
+                .find(|&x| *x > self.selection)
                 .unwrap_or(&(&open[0] + 1))
                 - 1;
         }
-        self.dice.roll();
-        self.game.save_roll(self.dice.result());
+        let open = self.game.open_tiles();
+        match self.rules.one_die {
+            OneDie::May if self.rules.one_die_allowed(open) => {
+                // Let the player pick how many dice to roll
+                self.choosing_dice = true;
+                self.tasks = StatefulList::with_items(DICE_TASKS.to_vec());
+                self.tasks.state.select(Some(0));
+            }
+            _ => self.manual_roll(self.rules.one_die_allowed(open)),
+        }
+    }
+
+    fn manual_roll(&mut self, one_die: bool) {
+        self.choosing_dice = false;
+        self.tasks = StatefulList::with_items(MANUAL_TASKS.to_vec());
+        if one_die {
+            self.dice.roll_one();
+        } else {
+            self.dice.roll();
+        }
+        self.game.save_dice(&self.dice);
         if self.game.check_loss(self.dice.result()) {
             self.manual_end_game(false);
             self.game.check_loss(self.dice.result());
@@ -176,9 +428,18 @@ impl<'a> App<'a> {
     }
 
     fn manual_new_game(&mut self) {
+        self.manual_record();
+        self.undo.clear();
+        self.redo.clear();
+        if let Some(hotseat) = &mut self.hotseat {
+            if hotseat.round_over() {
+                hotseat.new_round();
+            }
+        }
         self.gameover = false;
-        self.game = ShutTheBox::init(12);
+        self.game = ShutTheBox::with_rules(&self.rules);
         self.selection = 0;
+        self.seed_game();
         self.manual_reroll();
     }
 
@@ -190,59 +451,82 @@ impl<'a> App<'a> {
                     Some(0) => {
                         self.state = AppState::ManualGame;
                         self.tasks = StatefulList::with_items(MANUAL_TASKS.to_vec());
+                        self.reset_dice();
                         self.manual_new_game();
                     }
                     Some(1) => {
+                        // Hot-seat: players take turns with the same dice
+                        self.hotseat = HotSeat::new(&self.players, self.score_rule).ok();
+                        self.state = AppState::ManualGame;
+                        self.tasks = StatefulList::with_items(MANUAL_TASKS.to_vec());
+                        self.reset_dice();
+                        self.manual_new_game();
+                    }
+                    Some(2) => {
                         // Auto 1x
                         self.state = AppState::Auto1x;
                         self.tasks = StatefulList::with_items(AUTO_TASKS.to_vec());
                         self.tasks.state.select(Some(0));
-                        self.game = ShutTheBox::init(12);
-                        self.dice.roll();
+                        self.game = ShutTheBox::with_rules(&self.rules);
+                        self.bot = self.strategy.build(&self.rules);
+                        self.reset_dice();
+                        self.seed_game();
+                        self.bot_roll();
                     }
-                    Some(2) => {
+                    Some(3) => {
                         // Auto 10x
                         self.state = AppState::Auto10x;
+                        self.bot = self.strategy.build(&self.rules);
+                        self.reset_dice();
                         self.tasks = StatefulList::with_items(AUTO_TASKS.to_vec());
                         self.tasks.state.select(Some(0));
                     }
-                    Some(3) => {
+                    Some(4) => {
                         //  Auto Ludicrous
                         self.state = AppState::AutoFast;
                         self.tasks = StatefulList::with_items(AUTO_TASKS.to_vec());
                         self.tasks.state.select(Some(0));
                         self.selection = 0;
-                        self.thread_cancel.store(false, Ordering::SeqCst);
-                        let worker_cancel_flag = self.thread_cancel.clone();
-                        let worker_stats = self.stats.clone();
-                        self.thread_handles.push(spawn(move || {
-                            run_simulations(worker_stats, worker_cancel_flag)
-                        }));
+                        self.spawn_workers(1);
                     }
-                    Some(4) => {
+                    Some(5) => {
                         //  Auto Plaid
                         self.state = AppState::AutoPlaid;
                         self.tasks = StatefulList::with_items(AUTO_TASKS.to_vec());
                         self.tasks.state.select(Some(0));
                         self.selection = 0;
-                        self.thread_cancel.store(false, Ordering::SeqCst);
-                        for _ in 0..num_cpus::get() {
-                            let worker_cancel_flag = self.thread_cancel.clone();
-                            let worker_stats = self.stats.clone();
-                            self.thread_handles.push(spawn(move || {
-                                run_simulations(worker_stats, worker_cancel_flag)
-                            }));
-                        }
+                        self.spawn_workers(num_cpus::get());
+                    }
+                    Some(6) => {
+                        // Every strategy plays the same dice as the selected one
+                        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+                        let strategies = lineup(self.strategy, self.rules.tiles);
+                        self.tournament = Some(Tournament::new(
+                            &strategies,
+                            self.rules.clone(),
+                            self.score_rule,
+                            seed,
+                        ));
+                        self.state = AppState::Compare;
+                        self.tasks = StatefulList::with_items(AUTO_TASKS.to_vec());
+                        self.tasks.state.select(Some(0));
                     }
                     _ => {}
                 }
             }
+            AppState::ManualGame if self.choosing_dice => match self.tasks.state.selected() {
+                Some(0) => self.manual_roll(true),
+                Some(1) => self.manual_roll(false),
+                Some(2) => self.return_to_menu(),
+                _ => {}
+            },
             AppState::ManualGame => {
                 // Check if tasks are selected
                 match self.tasks.state.selected() {
                     None => {
                         // BOXES Selected
                         // If box selection is in staging vector, remove it
+                        self.rejected = None;
                         match self.staging.iter().position(|&x| x == self.selection) {
                             Some(idx) => {
                                 self.staging.remove(idx);
@@ -257,80 +541,316 @@ impl<'a> App<'a> {
                         if self.gameover {
                             self.manual_new_game();
                         } else {
-                            if self.staging.iter().fold(0, |acc, x| acc + x + 1)
-                                == self.dice.result()
-                            {
-                                // Lock! Shut the boxes
-                                for val in self.staging.iter() {
-                                    self.game.shut(*val + 1);
-                                }
-                                self.manual_reroll();
+                            // Lock! Shut the boxes
+                            let tiles: Vec<usize> = self.staging.iter().map(|x| x + 1).collect();
+                            self.rejected = self.game.try_play(TileSet::from_tiles(&tiles)).err();
+                            if self.rejected.is_some() {
+                                return;
                             }
+                            self.undo.push(ManualMove {
+                                before: self.dice.values.clone(),
+                                tiles,
+                                rolls: self.game.get_rolls().len(),
+                                after: None,
+                            });
+                            self.redo.clear();
+                            self.manual_reroll();
                         }
                     }
-                    Some(1) => {
+                    Some(1) if self.gameover && self.hotseat.is_none() => {
+                        self.start_review();
+                    }
+                    Some(_) => {
                         // Return to main menu!
                         self.return_to_menu();
                     }
-                    _ => {}
                 }
             }
-            AppState::Auto1x | AppState::Auto10x => {
+            AppState::Review => {
+                if let Some(0) = self.tasks.state.selected() {
+                    self.end_review();
+                }
+            }
+            AppState::Replay => {
+                if let Some(0) = self.tasks.state.selected() {
+                    self.return_to_menu();
+                }
+            }
+            AppState::Auto1x | AppState::Auto10x | AppState::Compare => {
                 // Start selected game
-                match self.tasks.state.selected() {
-                    Some(0) => {
-                        // Return to main menu!
-                        self.return_to_menu();
-                    }
-                    _ => {}
+                if let Some(0) = self.tasks.state.selected() {
+                    // Return to main menu!
+                    self.return_to_menu();
                 }
             }
             AppState::AutoFast | AppState::AutoPlaid => {
-                match self.tasks.state.selected() {
-                    Some(0) => {
-                        // Return to main menu!
-                        self.thread_cancel.store(true, Ordering::SeqCst);
-                        for handle in self.thread_handles.drain(..) {
-                            handle.join().unwrap();
-                        }
-                        self.return_to_menu();
+                if let Some(0) = self.tasks.state.selected() {
+                    // Return to main menu!
+                    self.thread_cancel.store(true, Ordering::SeqCst);
+                    for handle in self.thread_handles.drain(..) {
+                        handle.join().unwrap();
                     }
-                    _ => {}
+                    self.return_to_menu();
                 }
             }
-            _ => {}
         }
     }
 
-    pub fn on_right(&mut self) {
-        match self.state {
-            AppState::ManualGame => {
-                if self.tasks.state.selected().is_some() {
-                    // Dont move left/right when boxes are selected
-                    return;
+    /// Cycle the hint overlay through each solver objective and back off
+    fn next_hint(&mut self) {
+        self.hint = match self.hint {
+            None => Some(Objective::ALL[0]),
+            Some(objective) => Objective::ALL
+                .iter()
+                .skip_while(|&&x| x != objective)
+                .nth(1)
+                .copied(),
+        };
+    }
+
+    /// Best move for the current roll according to the hint solver
+    pub fn hint_best(&mut self) -> Option<TileSet> {
+        let (open, roll) = (self.game.open_tiles(), self.dice.result());
+        self.hint_solver()?.best_move(open, roll)
+    }
+
+    /// Every legal move for the current roll with the solver's value after it, best first
+    pub fn hint_moves(&mut self) -> Vec<(TileSet, f64)> {
+        let open = self.game.open_tiles();
+        let legal: Vec<TileSet> = self.game.legal_moves(self.dice.result()).collect();
+        let solver = match self.hint_solver() {
+            Some(solver) => solver,
+            None => return Vec::new(),
+        };
+        let objective = solver.objective();
+        let mut moves: Vec<(TileSet, f64)> = legal
+            .into_iter()
+            .map(|tiles| (tiles, solver.move_value(open, tiles)))
+            .collect();
+        moves.sort_by(|a, b| {
+            if objective.better(a.1, b.1) {
+                cmp::Ordering::Less
+            } else if objective.better(b.1, a.1) {
+                cmp::Ordering::Greater
+            } else {
+                cmp::Ordering::Equal
+            }
+        });
+        moves
+    }
+
+    /// Solver for the hint overlay, if hints are shown for a roll waiting to be played
+    fn hint_solver(&mut self) -> Option<&Solver> {
+        let objective = self.hint?;
+        let waiting = self.state == AppState::ManualGame && !self.gameover && !self.choosing_dice;
+        if !waiting || self.hotseat.is_some() {
+            return None;
+        }
+        self.solver(objective)
+    }
+
+    /// Solver for the current rules, rebuilt only when the objective changes
+    fn solver(&mut self, objective: Objective) -> Option<&Solver> {
+        if self.rules.tiles > MAX_SOLVER_TILES {
+            return None;
+        }
+        if self.hint_solver.as_ref().map(|x| x.objective()) != Some(objective) {
+            self.hint_solver = Some(Solver::new(&self.rules, objective));
+        }
+        self.hint_solver.as_ref()
+    }
+
+    /// Rate every roll of the finished game against the best move
+    fn start_review(&mut self) {
+        if self.solver(Objective::WinProbability).is_none() {
+            return;
+        }
+        self.review = review(&self.game, self.hint_solver.as_ref().unwrap());
+        self.review_idx = 0;
+        self.state = AppState::Review;
+        self.tasks = StatefulList::with_items(REVIEW_TASKS.to_vec());
+        self.tasks.state.select(Some(0));
+    }
+
+    /// Go back to the finished game
+    fn end_review(&mut self) {
+        let items = if self.game.victory() {
+            WON_TASKS
+        } else {
+            LOST_TASKS
+        };
+        self.review.clear();
+        self.state = AppState::ManualGame;
+        self.tasks = StatefulList::with_items(items.to_vec());
+        self.tasks.state.select(Some(1));
+    }
+
+    /// Review of the move being shown and the solver it was rated with
+    pub fn review_move(&self) -> Option<(&MoveReview, &Solver)> {
+        Some((
+            self.review.get(self.review_idx)?,
+            self.hint_solver.as_ref()?,
+        ))
+    }
+
+    /// Step through a saved game, switching to its rules
+    pub fn load_replay(&mut self, replay: Replay) {
+        self.set_rules(replay.rules.clone());
+        self.replay = Some(replay);
+        self.state = AppState::Replay;
+        self.tasks = StatefulList::with_items(REPLAY_TASKS.to_vec());
+        self.tasks.state.select(Some(0));
+        self.show_replay_step(0);
+    }
+
+    /// Show the board before move `step`, with the roll and the tiles it shut
+    fn show_replay_step(&mut self, step: usize) {
+        let replay = match &self.replay {
+            Some(replay) => replay,
+            None => return,
+        };
+        self.replay_step = cmp::min(step, replay.moves.len());
+        self.game = replay.game_at(self.replay_step);
+        self.staging.clear();
+        if let Some(played) = replay.moves.get(self.replay_step) {
+            self.dice.values = played.dice.to_vec();
+            self.staging = played.tiles.iter().map(|x| x - 1).collect();
+        }
+    }
+
+    /// Replay of the game on screen, or of the last finished autoplay game, with its dice seed
+    fn current_replay(&self) -> Result<Replay, &'static str> {
+        let (game, seed) = match self.state {
+            AppState::ManualGame | AppState::Review | AppState::Auto10x => {
+                (&self.game, self.game_seed)
+            }
+            AppState::Auto1x => match &self.last_game {
+                Some((game, seed)) => (game, *seed),
+                None => return Err("No game has finished yet"),
+            },
+            AppState::AutoFast | AppState::AutoPlaid => {
+                return Err("Games played in the background cannot be saved")
+            }
+            _ => return Err("There is no game to save"),
+        };
+        if game.moves().is_empty() {
+            return Err("There is no game to save");
+        }
+        Ok(Replay::new(game, self.rules.clone(), Some(seed)))
+    }
+
+    /// Write `current_replay` to a replay file
+    fn save_replay(&mut self) {
+        let replay = match self.current_replay() {
+            Ok(replay) => replay,
+            Err(reason) => {
+                self.status = Some(reason.to_string());
+                return;
+            }
+        };
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_millis());
+        let path = format!("replay-{}.json", millis);
+        let saved = replay
+            .to_json()
+            .map_err(|err| err.to_string())
+            .and_then(|text| fs::write(&path, text).map_err(|err| err.to_string()));
+        self.status = Some(match saved {
+            Ok(()) => format!("Saved {}", path),
+            Err(err) => format!("Could not save replay: {}", err),
+        });
+    }
+
+    /// Take back the last locked move and return to the roll it was played on
+    pub fn manual_undo(&mut self) {
+        if self.state != AppState::ManualGame || self.hotseat.is_some() {
+            // Hot-seat players cannot take back moves
+            return;
+        }
+        let mut last = match self.undo.pop() {
+            Some(last) => last,
+            None => return,
+        };
+        if self.game.get_rolls().len() > last.rolls {
+            self.game.pop_roll();
+            last.after = Some(self.dice.values.clone());
+        }
+        for &val in last.tiles.iter() {
+            self.game.unshut(val);
+        }
+        self.dice.values = last.before.clone();
+        self.selection = last.tiles[0] - 1;
+        self.redo.push(last);
+        self.gameover = false;
+        self.choosing_dice = false;
+        self.staging.clear();
+        self.tasks = StatefulList::with_items(MANUAL_TASKS.to_vec());
+    }
+
+    /// Play an undone move again, with the same dice that followed it
+    pub fn manual_redo(&mut self) {
+        if self.state != AppState::ManualGame || self.hotseat.is_some() {
+            return;
+        }
+        let next = match self.redo.pop() {
+            Some(next) => next,
+            None => return,
+        };
+        for &val in next.tiles.iter() {
+            self.game.shut(val);
+        }
+        let after = next.after;
+        self.undo.push(ManualMove {
+            after: None,
+            ..next
+        });
+        self.staging.clear();
+        match after {
+            Some(values) => {
+                self.dice.values = values;
+                self.choosing_dice = false;
+                self.tasks = StatefulList::with_items(MANUAL_TASKS.to_vec());
+                self.game.save_dice(&self.dice);
+                if self.game.check_loss(self.dice.result()) {
+                    self.manual_end_game(false);
                 }
+            }
+            None => self.manual_reroll(),
+        }
+    }
+
+    pub fn on_right(&mut self) {
+        if self.state == AppState::Replay {
+            self.show_replay_step(self.replay_step + 1);
+        } else if self.state == AppState::Review {
+            self.review_idx = cmp::min(self.review_idx + 1, self.review.len().saturating_sub(1));
+        } else if self.state == AppState::ManualGame {
+            if self.tasks.state.selected().is_some() {
+                // Dont move left/right when boxes are selected
+                return;
+            }
+            self.select_next();
+            while self.game.get_status(self.selection + 1).unwrap() {
                 self.select_next();
-                while self.game.get_status(self.selection + 1).unwrap() {
-                    self.select_next();
-                }
             }
-            _ => {}
         }
     }
 
     pub fn on_left(&mut self) {
-        match self.state {
-            AppState::ManualGame => {
-                if self.tasks.state.selected().is_some() {
-                    // Dont move left/right when boxes are selected
-                    return;
-                }
+        if self.state == AppState::Replay {
+            self.show_replay_step(self.replay_step.saturating_sub(1));
+        } else if self.state == AppState::Review {
+            self.review_idx = self.review_idx.saturating_sub(1);
+        } else if self.state == AppState::ManualGame {
+            if self.tasks.state.selected().is_some() {
+                // Dont move left/right when boxes are selected
+                return;
+            }
+            self.select_prev();
+            while self.game.get_status(self.selection + 1).unwrap() {
                 self.select_prev();
-                while self.game.get_status(self.selection + 1).unwrap() {
-                    self.select_prev();
-                }
             }
-            _ => {}
         }
     }
 
@@ -345,6 +865,76 @@ impl<'a> App<'a> {
                     self.plotidx = 0;
                 }
             }
+            'r' => {
+                self.score_rule = self.score_rule.next();
+            }
+            '+' if self.state == AppState::Idle && self.players.len() < MAX_PLAYERS => {
+                let name = format!("Player {}", self.players.len() + 1);
+                self.players.push(name);
+            }
+            '-' if self.state == AppState::Idle && self.players.len() > MIN_PLAYERS => {
+                self.players.pop();
+            }
+            's' if self.state == AppState::Idle => {
+                // Only switch strategies from the main menu
+                self.strategy = self.strategy.next();
+                while !self.strategy.supports(self.rules.tiles) {
+                    self.strategy = self.strategy.next();
+                }
+                self.exact = exact_outcomes(self.strategy, &self.rules);
+                // Games from the old strategy would not match the new exact outcomes
+                self.stats = Arc::new(Mutex::new(Statistics::for_rules(&self.rules)));
+            }
+            'b' if self.state == AppState::Idle => {
+                let next = BOARD_SIZES
+                    .iter()
+                    .find(|&&x| x > self.rules.tiles)
+                    .unwrap_or(&BOARD_SIZES[0]);
+                self.set_rules(Rules {
+                    tiles: *next,
+                    ..self.rules.clone()
+                });
+            }
+            'o' if self.state == AppState::Idle => {
+                self.set_rules(Rules {
+                    one_die: self.rules.one_die.next(),
+                    ..self.rules.clone()
+                });
+            }
+            'm' if self.state == AppState::Idle => {
+                let idx = MAX_SHUT_CHOICES
+                    .iter()
+                    .position(|&x| x == self.rules.max_shut)
+                    .map_or(0, |x| x + 1);
+                self.set_rules(Rules {
+                    max_shut: MAX_SHUT_CHOICES[idx % MAX_SHUT_CHOICES.len()],
+                    ..self.rules.clone()
+                });
+            }
+            'd' if self.state == AppState::Idle => {
+                let dice = &self.rules.dice;
+                let idx = DICE_CHOICES
+                    .iter()
+                    .position(|&x| x == (dice.count, dice.sides))
+                    .map_or(0, |x| x + 1);
+                let (count, sides) = DICE_CHOICES[idx % DICE_CHOICES.len()];
+                self.set_rules(Rules {
+                    dice: DiceConfig::fair(count, sides),
+                    ..self.rules.clone()
+                });
+            }
+            'h' => {
+                self.next_hint();
+            }
+            'u' => {
+                self.manual_undo();
+            }
+            'y' => {
+                self.manual_redo();
+            }
+            'w' => {
+                self.save_replay();
+            }
             '\n' => {
                 self.on_enter();
             }
@@ -358,38 +948,46 @@ impl<'a> App<'a> {
                 // Play one roll at a time
                 if self.game.victory() {
                     // Won
-                    self.stats.lock().unwrap().save_game(&self.game);
-                    self.game = ShutTheBox::init(12);
-                    self.dice.roll();
+                    self.auto_end_game();
                 }
-                let valid = self.game.play_roll(self.dice.result());
+                let valid = match self.game.try_play_dice(&self.dice, self.bot.as_mut()) {
+                    Ok(valid) => valid,
+                    Err(err) => {
+                        self.rejected = Some(err);
+                        false
+                    }
+                };
                 if valid {
-                    self.dice.roll();
+                    self.bot_roll();
                 } else {
                     // Lost
-                    self.stats.lock().unwrap().save_game(&self.game);
-                    self.game = ShutTheBox::init(12);
-                    self.dice.roll();
+                    self.auto_end_game();
                 }
             }
             AppState::Auto10x => {
                 // Play one game at a time
-                self.game = simulate_game();
+                self.seed_game();
+                self.game = simulate_game(self.bot.as_mut(), &mut self.dice, &self.rules);
                 self.stats.lock().unwrap().save_game(&self.game);
             }
             AppState::AutoFast => {
-                self.game = ShutTheBox::init(12);
+                self.game = ShutTheBox::with_rules(&self.rules);
                 self.game.shut(self.selection + 1);
                 self.selection += 1;
-                if self.selection >= 12 {
+                if self.selection >= self.rules.tiles {
                     self.selection = 0;
                 }
             }
+            AppState::Compare => {
+                if let Some(tournament) = &mut self.tournament {
+                    tournament.play(COMPARE_GAMES);
+                }
+            }
             AppState::AutoPlaid => {
-                self.game = ShutTheBox::init(12);
+                self.game = ShutTheBox::with_rules(&self.rules);
                 self.game.shut(self.selection + 1);
                 self.selection += 2;
-                if self.selection >= 12 {
+                if self.selection >= self.rules.tiles {
                     self.selection = 0;
                 }
             }
@@ -397,6 +995,15 @@ impl<'a> App<'a> {
         }
     }
 
+    /// Save a finished Auto1x game, keep it for replays and start the next one
+    fn auto_end_game(&mut self) {
+        self.stats.lock().unwrap().save_game(&self.game);
+        let next = ShutTheBox::with_rules(&self.rules);
+        self.last_game = Some((std::mem::replace(&mut self.game, next), self.game_seed));
+        self.seed_game();
+        self.bot_roll();
+    }
+
     fn select_next(&mut self) {
         self.selection += 1;
         if self.selection == self.game.total {
@@ -463,3 +1070,139 @@ impl<T> StatefulList<T> {
         self.state.select(None);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// App started from main menu entry `task`
+    fn start(task: usize, seed: Option<u64>) -> App<'static> {
+        let mut app = App::new("test");
+        app.seed = seed;
+        app.tasks.state.select(Some(task));
+        app.on_enter();
+        app
+    }
+
+    /// Check that a replay's seed plays the same game again
+    fn assert_reproduces(app: &App, replay: &Replay) {
+        let mut strategy = app.strategy.build(&replay.rules);
+        let game = simulate_seeded(strategy.as_mut(), replay.seed.unwrap(), &replay.rules);
+        assert_eq!(game.moves(), &replay.moves[..]);
+    }
+
+    #[test]
+    fn test_replay_seed() {
+        for seed in [Some(7), None] {
+            let mut app = start(3, seed);
+            app.on_tick();
+            app.on_tick();
+            assert_reproduces(&app, &app.current_replay().unwrap());
+            let mut app = start(2, seed);
+            assert!(app.current_replay().is_err());
+            while app.last_game.is_none() {
+                app.on_tick();
+            }
+            assert_reproduces(&app, &app.current_replay().unwrap());
+        }
+        assert!(App::new("test").current_replay().is_err());
+    }
+
+    /// Seeded manual game on a board of six tiles, waiting to play the first roll
+    fn small_board() -> App<'static> {
+        let mut app = App::new("test");
+        app.set_rules(Rules {
+            tiles: 6,
+            ..Rules::default()
+        });
+        app.seed = Some(1);
+        app.tasks.state.select(Some(0));
+        app.on_enter();
+        app
+    }
+
+    /// Stage and lock a legal move for the roll, returning its tiles
+    fn lock_move(app: &mut App) -> Vec<usize> {
+        let tiles = app.game.legal_moves(app.dice.result()).next().unwrap();
+        app.staging = tiles.iter().map(|x| x - 1).collect();
+        app.tasks.state.select(Some(0));
+        app.on_enter();
+        tiles.iter().collect()
+    }
+
+    #[test]
+    fn test_hints() {
+        let mut app = small_board();
+        app.dice.values = vec![3, 4];
+        assert!(app.hint_moves().is_empty());
+        assert_eq!(app.hint_best(), None);
+        let open = app.game.open_tiles();
+        for &objective in Objective::ALL.iter() {
+            app.next_hint();
+            assert_eq!(app.hint, Some(objective));
+            let mut solver = Solver::new(&app.rules, objective);
+            let moves = app.hint_moves();
+            let mut tiles: Vec<TileSet> = moves.iter().map(|x| x.0).collect();
+            tiles.sort_by_key(|x| x.0);
+            let mut legal: Vec<TileSet> = app.game.legal_moves(7).collect();
+            legal.sort_by_key(|x| x.0);
+            assert_eq!(tiles, legal);
+            assert_eq!(tiles.len(), 4);
+            for (tiles, value) in moves.iter() {
+                assert_eq!(*value, solver.move_value(open, *tiles));
+            }
+            for pair in moves.windows(2) {
+                assert!(!objective.better(pair[1].1, pair[0].1));
+            }
+            assert_eq!(app.hint_best(), solver.choose(open, 7));
+            assert_eq!(app.hint_best(), Some(moves[0].0));
+        }
+        app.next_hint();
+        assert_eq!(app.hint, None);
+        assert!(app.hint_moves().is_empty());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut app = small_board();
+        let full = TileSet::full(6);
+        app.manual_undo();
+        assert_eq!(app.game.moves().len(), 1);
+        let before = app.dice.values.clone();
+        let tiles = lock_move(&mut app);
+        let shut = full.difference(TileSet::from_tiles(&tiles));
+        assert_eq!(app.game.open_tiles(), shut);
+        // Six tiles cannot all be shut by one roll, so the dice were rolled again
+        assert_eq!(app.game.get_rolls().len(), 2);
+        let after = app.dice.values.clone();
+        let gameover = app.gameover;
+        app.staging.push(5);
+
+        app.manual_undo();
+        assert_eq!(app.game.open_tiles(), full);
+        assert_eq!(app.game.get_rolls().len(), 1);
+        assert_eq!(app.dice.values, before);
+        assert!(app.staging.is_empty());
+        assert_eq!(app.selection, tiles[0] - 1);
+        assert!(!app.gameover);
+        assert_eq!((app.undo.len(), app.redo.len()), (0, 1));
+        app.manual_undo();
+        assert_eq!(app.redo.len(), 1);
+
+        app.manual_redo();
+        assert_eq!(app.game.open_tiles(), shut);
+        assert_eq!(app.game.get_rolls().len(), 2);
+        assert_eq!(app.game.moves()[0].tiles.len(), tiles.len());
+        assert_eq!(app.dice.values, after);
+        assert_eq!(app.gameover, gameover);
+        assert_eq!((app.undo.len(), app.redo.len()), (1, 0));
+        app.manual_redo();
+        assert_eq!(app.undo.len(), 1);
+
+        // Locking a move after an undo drops the moves that could be redone
+        app.manual_undo();
+        lock_move(&mut app);
+        assert_eq!((app.undo.len(), app.redo.len()), (1, 0));
+        assert_eq!(app.game.open_tiles(), shut);
+    }
+}
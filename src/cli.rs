@@ -0,0 +1,492 @@
+use rand::Rng;
+use shutthebox::dice::{Dice, DiceConfig};
+use shutthebox::game::{simulate_batch, simulate_game, simulate_until, Statistics};
+use shutthebox::hotseat::{MAX_PLAYERS, MIN_PLAYERS};
+use shutthebox::interval::{IntervalMethod, CONFIDENCE};
+use shutthebox::rules::Rules;
+use shutthebox::score::ScoreRule;
+use shutthebox::strategy::StrategyKind;
+use shutthebox::tournament::{lineup, PairedDiff, Tournament};
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+pub const USAGE: &str = "Usage:
+    shutthebox [play] [RULES] [--strategy NAME] [--seed N] [--players NAME,NAME...]
+    shutthebox simulate [--games N] [--threads N] [RULES] [--strategy NAME] [--seed N]
+                        [--precision PCT] [--json FILE] [--csv DIR]
+    shutthebox merge FILE... [--json FILE] [--csv DIR]
+    shutthebox bench [--games N] [RULES] [--strategy NAME] [--seed N]
+    shutthebox compare [--games N] [--threads N] [RULES] [--strategies NAME,NAME...]
+                       [--score RULE] [--seed N]
+    shutthebox replay FILE
+
+Rules: --tiles N (default 12), --one-die never|may|must (once every tile above a die is shut),
+       --max-shut N (most tiles one roll may shut, default any),
+       --dice NdS (N dice of S sides, default 2d6), --weights W,W... (one per side, loaded dice)
+Precision: stop once the win rate is known to +/- PCT percent, playing at most --games games
+Compare: plays every strategy on the same dice, against the first (default --strategy)
+Scores: sum, digits, count, golf
+Strategies: greedy, highest, fewest, most, random, optimal, optimal-sum, optimal-digits";
+
+/// Command line options shared by every subcommand
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub games: u64,
+    pub threads: usize,
+    pub rules: Rules,
+    pub strategy: StrategyKind,
+    pub strategies: Vec<StrategyKind>,
+    pub score_rule: ScoreRule,
+    pub seed: Option<u64>,
+    pub precision: Option<f64>,
+    pub json: Option<String>,
+    pub csv: Option<String>,
+    pub files: Vec<String>,
+    pub players: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            games: 1_000_000,
+            threads: num_cpus::get(),
+            rules: Rules::default(),
+            strategy: StrategyKind::Greedy,
+            strategies: Vec::new(),
+            score_rule: ScoreRule::Sum,
+            seed: None,
+            precision: None,
+            json: None,
+            csv: None,
+            files: Vec::new(),
+            players: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    /// Parse `--flag value` pairs and positional files following the subcommand
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            if !flag.starts_with('-') {
+                options.files.push(flag.clone());
+                continue;
+            }
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            let invalid = || format!("Invalid value for {}: {}", flag, value);
+            match flag.as_str() {
+                "--games" | "-n" => options.games = value.parse().map_err(|_| invalid())?,
+                "--threads" | "-j" => options.threads = value.parse().map_err(|_| invalid())?,
+                "--tiles" | "-t" => options.rules.tiles = value.parse().map_err(|_| invalid())?,
+                "--max-shut" => {
+                    options.rules.max_shut = Some(value.parse().map_err(|_| invalid())?)
+                }
+                "--one-die" => options.rules.one_die = value.parse().map_err(to_string)?,
+                "--dice" => {
+                    let dice: DiceConfig = value.parse().map_err(to_string)?;
+                    options.rules.dice.count = dice.count;
+                    options.rules.dice.sides = dice.sides;
+                }
+                "--weights" => {
+                    let weights = value.split(',').map(|x| x.trim().parse());
+                    let weights = weights.collect::<Result<_, _>>().map_err(|_| invalid())?;
+                    options.rules.dice.weights = Some(weights);
+                }
+                "--strategy" | "-s" => options.strategy = value.parse().map_err(to_string)?,
+                "--strategies" => {
+                    options.strategies = value
+                        .split(',')
+                        .map(|x| x.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(to_string)?
+                }
+                "--score" => options.score_rule = value.parse().map_err(to_string)?,
+                "--seed" => options.seed = Some(value.parse().map_err(|_| invalid())?),
+                "--precision" => options.precision = Some(value.parse().map_err(|_| invalid())?),
+                "--json" => options.json = Some(value.clone()),
+                "--csv" => options.csv = Some(value.clone()),
+                "--players" => {
+                    options.players = value.split(',').map(|x| x.trim().to_string()).collect()
+                }
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+        if options.threads == 0 {
+            return Err("--threads must be at least 1".to_string());
+        }
+        if options.precision.is_some_and(|x| x <= 0.0 || x.is_nan()) {
+            return Err("--precision must be positive".to_string());
+        }
+        let nplayers = options.players.len();
+        if nplayers > 0 && !(MIN_PLAYERS..=MAX_PLAYERS).contains(&nplayers) {
+            return Err(format!(
+                "--players needs {} to {} names",
+                MIN_PLAYERS, MAX_PLAYERS
+            ));
+        }
+        options.rules.validate().map_err(to_string)?;
+        let tiles = options.rules.tiles;
+        let strategies = options.strategies.iter().chain(Some(&options.strategy));
+        if let Some(strategy) = strategies.into_iter().find(|x| !x.supports(tiles)) {
+            return Err(format!(
+                "Strategy {} cannot play {} tiles",
+                strategy.name(),
+                tiles
+            ));
+        }
+        Ok(options)
+    }
+}
+
+fn to_string(err: shutthebox::Error) -> String {
+    err.to_string()
+}
+
+/// Run a batch of games without the TUI and print a summary
+pub fn simulate(options: &Options) -> Result<(), Box<dyn Error>> {
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()?;
+    let start = Instant::now();
+    let (games, strategy, rules) = (options.games, options.strategy, &options.rules);
+    let stats = pool.install(|| match options.precision {
+        Some(pct) => simulate_until(pct / 100.0, games, seed, strategy, rules),
+        None => simulate_batch(games, seed, strategy, rules),
+    });
+    println!(
+        "Simulated {} games in {:.2?} ({}, strategy {}, seed {}, {} threads)",
+        stats.num_total,
+        start.elapsed(),
+        options.rules,
+        options.strategy.name(),
+        seed,
+        options.threads
+    );
+    if let Some(pct) = options.precision {
+        let reached = 100.0 * stats.win_interval(IntervalMethod::Wilson).half_width();
+        if reached > pct {
+            println!(
+                "Precision +/- {}% not reached within {} games (+/- {:.4}%)",
+                pct, games, reached
+            );
+        }
+    }
+    print!("{}", summary(&stats));
+    export(options, &stats)
+}
+
+/// Play several strategies on the same seeded dice and compare them with the first
+pub fn compare(options: &Options) -> Result<(), Box<dyn Error>> {
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let tiles = options.rules.tiles;
+    let strategies = match options.strategies.is_empty() {
+        false => options.strategies.clone(),
+        true => lineup(options.strategy, tiles),
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()?;
+    let start = Instant::now();
+    let mut tournament =
+        Tournament::new(&strategies, options.rules.clone(), options.score_rule, seed);
+    pool.install(|| tournament.play(options.games));
+    println!(
+        "Played {} games per strategy in {:.2?} ({}, seed {})",
+        tournament.games,
+        start.elapsed(),
+        options.rules,
+        seed
+    );
+    print!("{}", standings(&tournament));
+    Ok(())
+}
+
+/// Format the standings of a tournament, with differences from the baseline
+pub fn standings(tournament: &Tournament) -> String {
+    let mut out = String::new();
+    let games = tournament.games.max(1) as f64;
+    let baseline = tournament.standings[0].strategy.name();
+    writeln!(
+        out,
+        "{:<15} {:>8} {:>20} {:>8}   {:>8} {:>20} {:>8}",
+        "strategy",
+        "win %",
+        format!("vs {}", baseline),
+        "p",
+        tournament.rule.name(),
+        format!("vs {}", baseline),
+        "p"
+    )
+    .unwrap();
+    for (ii, standing) in tournament.standings.iter().enumerate() {
+        let diff = |diff: &PairedDiff, scale: f64| match ii {
+            0 => format!("{:>20} {:>8}", "-", "-"),
+            _ => format!(
+                "{:>+9.3} +/- {:<6.3} {:>8.4}",
+                scale * diff.mean(),
+                scale * diff.interval(CONFIDENCE).half_width(),
+                diff.p_value()
+            ),
+        };
+        writeln!(
+            out,
+            "{:<15} {:>8.3} {}   {:>8.3} {}",
+            standing.strategy.name(),
+            100.0 * standing.won as f64 / games,
+            diff(&standing.win_diff, 100.0),
+            standing.score as f64 / games,
+            diff(&standing.score_diff, 1.0)
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Measure single-threaded simulation throughput
+pub fn bench(options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut strategy = options.strategy.build(&options.rules);
+    let mut dice = Dice::with_seed(options.seed.unwrap_or(0)).with_config(&options.rules.dice);
+    let mut won = 0;
+    let start = Instant::now();
+    for _ in 0..options.games {
+        won += simulate_game(strategy.as_mut(), &mut dice, &options.rules).victory() as u64;
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{} games ({} won) in {:.2?}: {:.0} games/s (strategy {})",
+        options.games,
+        won,
+        elapsed,
+        options.games as f64 / elapsed.as_secs_f64(),
+        options.strategy.name()
+    );
+    Ok(())
+}
+
+/// Merge previously exported JSON statistics, in the order given
+pub fn merge(options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut files = options.files.iter();
+    let first = files.next().ok_or("merge needs at least one JSON file")?;
+    let mut stats = Statistics::from_json(&fs::read_to_string(first)?)?;
+    for path in files {
+        stats.try_merge(&Statistics::from_json(&fs::read_to_string(path)?)?)?;
+    }
+    print!("{}", summary(&stats));
+    export(options, &stats)
+}
+
+/// Write statistics to the files requested on the command line
+pub fn export(options: &Options, stats: &Statistics) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &options.json {
+        fs::write(path, stats.to_json()?)?;
+    }
+    if let Some(dir) = &options.csv {
+        fs::create_dir_all(dir)?;
+        for (name, table) in stats.csv_tables() {
+            fs::write(Path::new(dir).join(format!("{}.csv", name)), table)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_counts(out: &mut String, title: &str, counts: &[u64]) {
+    let total: u64 = counts.iter().sum();
+    writeln!(out, "\n{}", title).unwrap();
+    for (ii, count) in counts.iter().enumerate() {
+        writeln!(
+            out,
+            "  {:>3}: {:>10} ({:>6.2}%)",
+            ii + 1,
+            count,
+            100.0 * *count as f64 / total.max(1) as f64
+        )
+        .unwrap();
+    }
+}
+
+/// Format the same statistics the TUI plots as plain text
+pub fn summary(stats: &Statistics) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Wins: {} / {} ({:.4}%)",
+        stats.num_won,
+        stats.num_total,
+        100.0 * stats.num_won as f64 / stats.num_total.max(1) as f64
+    )
+    .unwrap();
+    for &method in IntervalMethod::ALL.iter() {
+        let interval = stats.win_interval(method);
+        writeln!(
+            out,
+            "  {:.0}% {}: {:.4}% to {:.4}% (+/- {:.4}%)",
+            100.0 * CONFIDENCE,
+            method.name(),
+            100.0 * interval.lo,
+            100.0 * interval.hi,
+            100.0 * interval.half_width()
+        )
+        .unwrap();
+    }
+    write_counts(&mut out, "Boxes Shut", &stats.count_shut);
+    writeln!(
+        out,
+        "\nBox Shut Probability ({:.0}% Wilson interval)",
+        100.0 * CONFIDENCE
+    )
+    .unwrap();
+    for tile in 1..=stats.count_shut.len() {
+        let interval = stats.shut_interval(tile, IntervalMethod::Wilson);
+        writeln!(
+            out,
+            "  {:>3}: {:>7.3}% to {:>7.3}%",
+            tile,
+            100.0 * interval.lo,
+            100.0 * interval.hi
+        )
+        .unwrap();
+    }
+    write_counts(&mut out, "Dice Roll Count", &stats.count_rawrolls);
+    write_counts(&mut out, "Number of Rolls Per Game", &stats.count_nrolls);
+    write_counts(&mut out, "Losing Roll", &stats.count_lastroll);
+    write_counts(&mut out, "Dice Thrown", &stats.count_ndice);
+    write_counts(&mut out, "Tiles Shut Per Roll", &stats.count_move_tiles);
+    writeln!(out, "\nScores (lower is better)").unwrap();
+    for &rule in ScoreRule::ALL.iter() {
+        let hist = stats.score_histogram(rule);
+        writeln!(
+            out,
+            "  {:>6}: mean {:.2}, p50 {}, p90 {}, max {}",
+            rule.name(),
+            hist.mean(),
+            hist.value_at_quantile(0.5),
+            hist.value_at_quantile(0.9),
+            hist.max()
+        )
+        .unwrap();
+    }
+    let hist = &stats.games_between_win;
+    writeln!(out, "\nGames Between Wins").unwrap();
+    if hist.is_empty() {
+        writeln!(out, "  (no wins)").unwrap();
+    } else {
+        writeln!(
+            out,
+            "  mean {:.1}, p50 {}, p90 {}, p99 {}, max {}",
+            hist.mean(),
+            hist.value_at_quantile(0.5),
+            hist.value_at_quantile(0.9),
+            hist.value_at_quantile(0.99),
+            hist.max()
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shutthebox::rules::OneDie;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = Options::parse(&args(&[
+            "--games",
+            "10",
+            "-j",
+            "2",
+            "--strategy",
+            "optimal",
+            "--seed",
+            "42",
+            "--one-die",
+            "may",
+        ]))
+        .unwrap();
+        assert_eq!(
+            options,
+            Options {
+                games: 10,
+                threads: 2,
+                rules: Rules {
+                    tiles: 12,
+                    one_die: OneDie::May,
+                    max_shut: None,
+                    dice: DiceConfig::default(),
+                },
+                strategy: StrategyKind::Optimal,
+                strategies: Vec::new(),
+                score_rule: ScoreRule::Sum,
+                seed: Some(42),
+                precision: None,
+                json: None,
+                csv: None,
+                files: Vec::new(),
+                players: Vec::new(),
+            }
+        );
+        assert_eq!(Options::parse(&[]).unwrap(), Options::default());
+        let options = Options::parse(&args(&["a.json", "--json", "c.json", "b.json"])).unwrap();
+        assert_eq!(options.files, vec!["a.json", "b.json"]);
+        assert_eq!(options.json, Some("c.json".to_string()));
+        let options = Options::parse(&args(&["--players", "Ann, Bo,Cy"])).unwrap();
+        assert_eq!(options.players, vec!["Ann", "Bo", "Cy"]);
+        let options = Options::parse(&args(&["--strategies", "most, greedy", "--score", "count"]));
+        let options = options.unwrap();
+        assert_eq!(
+            options.strategies,
+            vec![StrategyKind::MostTiles, StrategyKind::Greedy]
+        );
+        assert_eq!(options.score_rule, ScoreRule::Count);
+        let options = Options::parse(&args(&["--weights", "1,1,1,1,1,2", "--dice", "3d6"]));
+        let dice = options.unwrap().rules.dice;
+        assert_eq!((dice.count, dice.sides), (3, 6));
+        assert_eq!(dice.weights, Some(vec![1.0, 1.0, 1.0, 1.0, 1.0, 2.0]));
+    }
+
+    #[test]
+    fn test_parse_options_errors() {
+        assert!(Options::parse(&args(&["--games"])).is_err());
+        assert!(Options::parse(&args(&["--games", "many"])).is_err());
+        assert!(Options::parse(&args(&["--strategy", "lucky"])).is_err());
+        assert!(Options::parse(&args(&["--threads", "0"])).is_err());
+        assert!(Options::parse(&args(&["--bogus", "1"])).is_err());
+        assert!(Options::parse(&args(&["--tiles", "0"])).is_err());
+        assert!(Options::parse(&args(&["--tiles", "33"])).is_err());
+        assert!(Options::parse(&args(&["--one-die", "often"])).is_err());
+        assert!(Options::parse(&args(&["--players", "Solo"])).is_err());
+        assert!(Options::parse(&args(&["--precision", "0"])).is_err());
+        assert!(Options::parse(&args(&["--max-shut", "0"])).is_err());
+        assert!(Options::parse(&args(&["--dice", "2x6"])).is_err());
+        assert!(Options::parse(&args(&["--dice", "d8", "--weights", "1,2"])).is_err());
+        assert!(Options::parse(&args(&["--weights", "1,a,1,1,1,1"])).is_err());
+        assert!(Options::parse(&args(&["--precision", "0.01"])).is_ok());
+        assert!(Options::parse(&args(&["-t", "20", "-s", "optimal"])).is_err());
+        assert!(Options::parse(&args(&["-t", "20", "-s", "greedy"])).is_ok());
+        assert!(Options::parse(&args(&["-t", "20", "--strategies", "greedy,optimal"])).is_err());
+        assert!(Options::parse(&args(&["--strategies", "greedy,lucky"])).is_err());
+    }
+
+    #[test]
+    fn test_summary() {
+        let stats = simulate_batch(100, 1, StrategyKind::Greedy, &Rules::new(9));
+        let text = summary(&stats);
+        assert!(text.starts_with(&format!("Wins: {} / 100", stats.num_won)));
+        assert!(text.contains("Losing Roll"));
+        assert!(text.contains("    golf: mean"));
+        assert!(text.contains("95% clopper-pearson: "));
+    }
+}
@@ -0,0 +1,441 @@
+use crate::error::Error;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Most dice thrown in one roll
+pub const MAX_DICE: usize = 6;
+
+/// Most faces on one die
+pub const MAX_SIDES: usize = 20;
+
+/// DiceConfig describes the dice thrown for every roll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiceConfig {
+    /// Number of dice thrown together
+    pub count: usize,
+    /// Faces on each die, numbered from 1
+    pub sides: usize,
+    /// Relative weight of each face for loaded dice, or None for fair dice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<Vec<f64>>,
+}
+
+impl Default for DiceConfig {
+    fn default() -> Self {
+        DiceConfig::fair(2, 6)
+    }
+}
+
+impl DiceConfig {
+    /// `count` fair dice with `sides` faces each
+    pub fn fair(count: usize, sides: usize) -> DiceConfig {
+        DiceConfig {
+            count,
+            sides,
+            weights: None,
+        }
+    }
+
+    /// Check that the dice can be rolled
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |reason: String| Err(Error::Dice(reason));
+        if self.count == 0 || self.count > MAX_DICE {
+            return invalid(format!("rolls need 1 to {} dice", MAX_DICE));
+        }
+        if self.sides < 2 || self.sides > MAX_SIDES {
+            return invalid(format!("dice need 2 to {} sides", MAX_SIDES));
+        }
+        if let Some(weights) = &self.weights {
+            if weights.len() != self.sides {
+                return invalid(format!("{} sides need {} weights", self.sides, self.sides));
+            }
+            if weights.iter().any(|x| !x.is_finite() || *x < 0.0) {
+                return invalid("weights cannot be negative".to_string());
+            }
+            if weights.iter().all(|&x| x == 0.0) {
+                return invalid("some face needs a positive weight".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Largest sum of one roll of every die
+    pub fn max_sum(&self) -> usize {
+        self.count * self.sides
+    }
+
+    /// Probability of each result of a single die, indexed like `distribution`
+    pub fn one_die_distribution(&self) -> Vec<f64> {
+        let mut probs = vec![0.0; self.max_sum() + 1];
+        match &self.weights {
+            Some(weights) => {
+                let total: f64 = weights.iter().sum();
+                for (prob, weight) in probs[1..].iter_mut().zip(weights.iter()) {
+                    *prob = weight / total;
+                }
+            }
+            None => {
+                for prob in probs[1..=self.sides].iter_mut() {
+                    *prob = 1.0 / self.sides as f64;
+                }
+            }
+        }
+        probs
+    }
+
+    /// Probability of each roll result of every die, indexed by the sum of the dice
+    pub fn distribution(&self) -> Vec<f64> {
+        let face = self.one_die_distribution();
+        let mut probs = vec![0.0; self.max_sum() + 1];
+        probs[0] = 1.0;
+        // Add one die at a time, so sums are at most `dice * sides` after each
+        for dice in 1..=self.count {
+            for sum in (0..=dice * self.sides).rev() {
+                probs[sum] = (1..=self.sides.min(sum))
+                    .map(|val| probs[sum - val] * face[val])
+                    .sum();
+            }
+        }
+        probs
+    }
+
+    fn roll_face<R: RngCore + ?Sized>(&self, rng: &mut R) -> usize {
+        let weights = match &self.weights {
+            Some(weights) => weights,
+            None => return rng.gen_range(1, self.sides + 1),
+        };
+        let mut left = rng.gen::<f64>() * weights.iter().sum::<f64>();
+        for (face, &weight) in weights.iter().enumerate() {
+            if left < weight {
+                return face + 1;
+            }
+            left -= weight;
+        }
+        // Rounding can leave a sliver past the last face
+        weights.iter().rposition(|&x| x > 0.0).unwrap_or(0) + 1
+    }
+}
+
+impl fmt::Display for DiceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.weights.is_some() {
+            write!(f, " (loaded)")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DiceConfig {
+    type Err = Error;
+
+    /// Parse dice notation such as `3d6`, or `d8` for a single die
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (count, sides) = s.split_once('d').ok_or_else(|| Error::unknown("dice", s))?;
+        let count = match count {
+            "" => 1,
+            _ => count.parse().map_err(|_| Error::unknown("dice", s))?,
+        };
+        let sides = sides.parse().map_err(|_| Error::unknown("dice", s))?;
+        let config = DiceConfig::fair(count, sides);
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Faces shown by the dice of one roll, stored inline so recording a roll does not allocate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Faces {
+    values: [u8; MAX_DICE],
+    count: u8,
+}
+
+impl Faces {
+    pub fn new(values: &[usize]) -> Faces {
+        assert!(
+            values.len() <= MAX_DICE,
+            "Rolls throw at most {} dice",
+            MAX_DICE
+        );
+        let mut faces = Faces::default();
+        for (face, &val) in faces.values.iter_mut().zip(values) {
+            *face = u8::try_from(val).expect("Dice faces fit in a byte");
+        }
+        faces.count = values.len() as u8;
+        faces
+    }
+
+    /// Number of dice thrown
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.values[..self.len()].iter().map(|&x| x as usize)
+    }
+
+    pub fn to_vec(&self) -> Vec<usize> {
+        self.iter().collect()
+    }
+}
+
+/// Saved as the list of faces
+impl Serialize for Faces {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Faces {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Faces, D::Error> {
+        let values = Vec::<usize>::deserialize(deserializer)?;
+        if values.len() > MAX_DICE || values.iter().any(|&x| x > MAX_SIDES) {
+            return Err(D::Error::custom(format!(
+                "rolls throw at most {} dice of at most {} sides",
+                MAX_DICE, MAX_SIDES
+            )));
+        }
+        Ok(Faces::new(&values))
+    }
+}
+
+/// Where dice values come from
+// StdRng is kept inline since boxing it would cost every roll an indirect call
+#[allow(clippy::large_enum_variant)]
+enum DiceSource {
+    Random(StdRng),
+    /// Any other generator, only boxed since it is rarely used
+    Custom(Box<dyn RngCore + Send>),
+    Scripted(Vec<Vec<usize>>, usize),
+}
+
+/// Struct for handling a roll of several dice, two six-sided ones unless configured
+/// `values` holds one entry per die thrown by the last roll
+pub struct Dice {
+    source: DiceSource,
+    config: DiceConfig,
+    pub values: Vec<usize>,
+}
+
+impl Default for Dice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dice {
+    pub fn new() -> Dice {
+        Dice::with_source(DiceSource::Random(StdRng::from_entropy()))
+    }
+
+    /// Dice with a reproducible sequence of rolls
+    pub fn with_seed(seed: u64) -> Dice {
+        Dice::with_source(DiceSource::Random(StdRng::seed_from_u64(seed)))
+    }
+
+    /// Dice driven by any random number generator
+    pub fn with_rng<R: RngCore + Send + 'static>(rng: R) -> Dice {
+        Dice::with_source(DiceSource::Custom(Box::new(rng)))
+    }
+
+    fn with_source(source: DiceSource) -> Dice {
+        Dice {
+            source,
+            config: DiceConfig::default(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Dice that replay a fixed sequence of rolls, starting over when exhausted
+    pub fn scripted(rolls: Vec<Vec<usize>>) -> Dice {
+        assert!(!rolls.is_empty(), "Scripted dice need at least one roll");
+        Dice::with_source(DiceSource::Scripted(rolls, 0))
+    }
+
+    /// Throw `config` dice instead of two six-sided ones
+    pub fn with_config(mut self, config: &DiceConfig) -> Dice {
+        self.config = config.clone();
+        self
+    }
+
+    /// Dice thrown by every roll
+    pub fn config(&self) -> &DiceConfig {
+        &self.config
+    }
+
+    /// Roll every die
+    pub fn roll(&mut self) {
+        self.throw(self.config.count);
+    }
+
+    /// Roll only the first die
+    pub fn roll_one(&mut self) {
+        self.throw(1);
+    }
+
+    fn throw(&mut self, count: usize) {
+        let Dice {
+            source,
+            config,
+            values,
+        } = self;
+        values.clear();
+        match source {
+            DiceSource::Random(rng) => {
+                for _ in 0..count {
+                    values.push(config.roll_face(rng));
+                }
+            }
+            DiceSource::Custom(rng) => {
+                for _ in 0..count {
+                    values.push(config.roll_face(rng.as_mut()));
+                }
+            }
+            DiceSource::Scripted(rolls, idx) => {
+                values.extend(rolls[*idx].iter().take(count));
+                *idx = (*idx + 1) % rolls.len();
+            }
+        }
+    }
+
+    pub fn result(&self) -> usize {
+        self.values.iter().sum()
+    }
+
+    /// Number of dice thrown by the last roll
+    pub fn count(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn pprint(&self) -> String {
+        fn onedie(val: usize) -> &'static str {
+            match val {
+                1 => "⚀",
+                2 => "⚁",
+                3 => "⚂",
+                4 => "⚃",
+                5 => "⚄",
+                6 => "⚅",
+                _ => "?",
+            }
+        }
+        let faces: Vec<String> = match self.config.sides {
+            6 => self.values.iter().map(|&x| onedie(x).to_string()).collect(),
+            // Only six-sided dice have glyphs
+            _ => self.values.iter().map(|x| format!("[{}]", x)).collect(),
+        };
+        faces.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_dice_roll() {
+        let mut dice = Dice::new();
+        dice.roll();
+        assert_eq!(dice.count(), 2);
+        assert_eq!(dice.result(), dice.values[0] + dice.values[1]);
+    }
+
+    #[test]
+    fn test_dice_seeded() {
+        let mut dice1 = Dice::with_seed(42);
+        let mut dice2 = Dice::with_seed(42);
+        for _ in 0..100 {
+            dice1.roll();
+            dice2.roll();
+            assert_eq!(dice1.values, dice2.values);
+            assert!(dice1.result() >= 2 && dice1.result() <= 12);
+        }
+    }
+
+    #[test]
+    fn test_dice_scripted() {
+        let mut dice = Dice::scripted(vec![vec![1, 2], vec![6, 6]]);
+        dice.roll();
+        assert_eq!(dice.result(), 3);
+        dice.roll();
+        assert_eq!(dice.result(), 12);
+        dice.roll();
+        assert_eq!(dice.values, vec![1, 2]);
+        assert_eq!(dice.pprint(), "⚀ ⚁");
+    }
+
+    #[test]
+    fn test_dice_config() {
+        let mut dice = Dice::with_seed(3).with_config(&DiceConfig::fair(3, 8));
+        for _ in 0..100 {
+            dice.roll();
+            assert_eq!(dice.count(), 3);
+            assert!(dice.values.iter().all(|&x| (1..=8).contains(&x)));
+        }
+        dice.roll_one();
+        assert_eq!(dice.count(), 1);
+        assert!(dice.pprint().starts_with('['));
+
+        let loaded = DiceConfig {
+            weights: Some(vec![0.0, 0.0, 0.0, 0.0, 0.0, 1.0]),
+            ..DiceConfig::default()
+        };
+        let mut dice = Dice::with_seed(3).with_config(&loaded);
+        dice.roll();
+        assert_eq!(dice.values, vec![6, 6]);
+        assert!(close(loaded.distribution()[12], 1.0));
+    }
+
+    #[test]
+    fn test_distribution() {
+        let two = DiceConfig::default().distribution();
+        assert_eq!(two.len(), 13);
+        assert!(close(two[7], 6.0 / 36.0));
+        assert!(close(two[2], 1.0 / 36.0));
+        assert_eq!(two[1], 0.0);
+        let three = DiceConfig::fair(3, 6).distribution();
+        assert_eq!(three.len(), 19);
+        assert!(close(three[10], 27.0 / 216.0));
+        assert!(close(three.iter().sum(), 1.0));
+        let loaded = DiceConfig {
+            weights: Some(vec![1.0, 1.0, 2.0]),
+            ..DiceConfig::fair(1, 3)
+        };
+        assert!(close(loaded.distribution()[3], 0.5));
+        assert!(close(loaded.one_die_distribution()[1], 0.25));
+    }
+
+    #[test]
+    fn test_dice_parse() {
+        let dice: DiceConfig = "3d6".parse().unwrap();
+        assert_eq!(dice, DiceConfig::fair(3, 6));
+        assert_eq!(dice.to_string(), "3d6");
+        assert_eq!("d8".parse::<DiceConfig>().unwrap(), DiceConfig::fair(1, 8));
+        assert!("2x6".parse::<DiceConfig>().is_err());
+        assert!("0d6".parse::<DiceConfig>().is_err());
+        assert!("2d1".parse::<DiceConfig>().is_err());
+        let mut loaded = DiceConfig {
+            weights: Some(vec![1.0; 5]),
+            ..DiceConfig::default()
+        };
+        assert!(loaded.validate().is_err());
+        loaded.weights = Some(vec![0.0; 6]);
+        assert!(loaded.validate().is_err());
+        loaded.weights = Some(vec![1.0, 1.0, 1.0, 1.0, 1.0, 3.0]);
+        assert!(loaded.validate().is_ok());
+        assert_eq!(loaded.to_string(), "2d6 (loaded)");
+    }
+}
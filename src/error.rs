@@ -0,0 +1,128 @@
+use crate::hotseat::{MAX_PLAYERS, MIN_PLAYERS};
+use crate::tiles::MAX_TILES;
+use std::fmt;
+
+/// Error returned by the fallible parts of the library
+#[derive(Debug)]
+pub enum Error {
+    /// A name that does not match any score rule, strategy or other option
+    UnknownName { kind: &'static str, name: String },
+    /// A board size outside 1 to `MAX_TILES`
+    Tiles(usize),
+    /// A limit of no tiles shut per roll
+    MaxShut,
+    /// A hot-seat table with too few or too many players
+    Players(usize),
+    /// Dice that cannot be rolled, such as too many sides or bad weights
+    Dice(String),
+    /// A replay describing a game that could not have been played
+    Replay(String),
+    /// A file that is not valid JSON for what it should hold
+    Json(serde_json::Error),
+    /// An illegal move or a game that cannot be counted
+    Game(GameError),
+    /// Statistics gathered under different rules, which cannot be merged
+    Merge(String),
+}
+
+/// Reason a move was rejected or a game could not be saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// A tile below 1 or above the number of tiles on the board
+    TileOutOfRange(usize),
+    /// A tile that was shut earlier in the game
+    AlreadyShut(usize),
+    /// Tiles played before any roll was recorded
+    NoRoll,
+    /// Tiles played on a roll that already shut some
+    AlreadyPlayed,
+    /// Tiles that do not add up to the roll
+    WrongSum { roll: usize, sum: usize },
+    /// More tiles than the rules allow one roll to shut
+    TooManyTiles { max: usize, found: usize },
+    /// A roll no dice can throw
+    RollOutOfRange(usize),
+    /// A game saved into statistics for a different board size
+    BoardSize { expected: usize, found: usize },
+}
+
+/// Result of the fallible parts of the library
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub(crate) fn unknown(kind: &'static str, name: &str) -> Error {
+        Error::UnknownName {
+            kind,
+            name: name.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownName { kind, name } => write!(f, "Unknown {}: {}", kind, name),
+            Error::Tiles(tiles) => write!(f, "Boards need 1 to {} tiles, not {}", MAX_TILES, tiles),
+            Error::MaxShut => write!(f, "Each roll must be allowed to shut a tile"),
+            Error::Players(players) => write!(
+                f,
+                "Hot-seat games need {} to {} players, not {}",
+                MIN_PLAYERS, MAX_PLAYERS, players
+            ),
+            Error::Dice(reason) => write!(f, "Invalid dice: {}", reason),
+            Error::Replay(reason) => write!(f, "Invalid replay: {}", reason),
+            Error::Json(err) => write!(f, "Invalid JSON: {}", err),
+            Error::Game(err) => err.fmt(f),
+            Error::Merge(reason) => write!(f, "Cannot merge statistics: {}", reason),
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::TileOutOfRange(tile) => write!(f, "Tile {} is not on the board", tile),
+            GameError::AlreadyShut(tile) => write!(f, "Tile {} is already shut", tile),
+            GameError::NoRoll => write!(f, "Roll the dice first"),
+            GameError::AlreadyPlayed => write!(f, "This roll has already been played"),
+            GameError::WrongSum { roll, sum } => {
+                write!(f, "Tiles add up to {}, not the roll of {}", sum, roll)
+            }
+            GameError::TooManyTiles { max, found } => {
+                write!(f, "Only {} tiles may be shut per roll, not {}", max, found)
+            }
+            GameError::RollOutOfRange(roll) => {
+                write!(f, "No dice can roll {}", roll)
+            }
+            GameError::BoardSize { expected, found } => write!(
+                f,
+                "Game has {} tiles but the statistics are for {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Json(err) => Some(err),
+            Error::Game(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<GameError> for Error {
+    fn from(err: GameError) -> Error {
+        Error::Game(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Error {
+        Error::Json(err)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::rules::Rules;
+use crate::solver::MAX_SOLVER_TILES;
+use crate::strategy::Strategy;
+use crate::tiles::{subsets, TileSet};
+
+/// Outcomes holds the exact distributions that `Statistics` estimates by sampling
+///
+/// They are found by propagating probability through the Markov chain of board
+/// states under a deterministic strategy, indexed like the matching `Statistics`
+/// counts but as probabilities per game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcomes {
+    /// Probability of shutting every tile
+    pub win: f64,
+    /// Probability that each tile is shut at the end of the game
+    pub shut: Vec<f64>,
+    /// Expected number of times each roll is thrown in a game
+    pub rawrolls: Vec<f64>,
+    /// Probability of a game lasting each number of rolls
+    pub nrolls: Vec<f64>,
+    /// Probability of each roll being the last of the game, won or lost
+    pub lastroll: Vec<f64>,
+}
+
+impl Outcomes {
+    /// Propagate every game played with `rules` by a deterministic strategy
+    pub fn new(rules: &Rules, strategy: &mut dyn Strategy) -> Outcomes {
+        let total = rules.tiles;
+        assert!(
+            total <= MAX_SOLVER_TILES,
+            "Exact outcomes handle at most {} tiles",
+            MAX_SOLVER_TILES
+        );
+        let two = rules.dice.distribution();
+        let one = rules.dice.one_die_distribution();
+        let nstates = 1 << total;
+        let full = TileSet::full(total);
+        let mut out = Outcomes {
+            win: 0.0,
+            shut: vec![0.0; total],
+            rawrolls: vec![0.0; two.len() - 1],
+            nrolls: vec![0.0; total],
+            lastroll: vec![0.0; two.len() - 1],
+        };
+
+        // Probability of reaching each open mask after each number of rolls, which
+        // is below the number of tiles since every roll but the last shuts one
+        let mut mass = vec![0.0; nstates * total];
+        mass[(nstates - 1) * total] = 1.0;
+        // Shutting tiles always moves to a smaller mask, so visit in descending order
+        for mask in (1..nstates).rev() {
+            let (lower, upper) = mass.split_at_mut(mask * total);
+            let row = &upper[..total];
+            let reach: f64 = row.iter().sum();
+            if reach == 0.0 {
+                continue;
+            }
+            let open = TileSet(mask as u32);
+            let probs = if rules.roll_one_die(open, strategy) {
+                &one
+            } else {
+                &two
+            };
+            for (roll, &prob) in probs.iter().enumerate().filter(|x| *x.1 > 0.0) {
+                out.rawrolls[roll - 1] += reach * prob;
+                let moves = subsets(open, roll).at_most(rules.shut_limit());
+                let end = match strategy.choose_from(open, roll, moves) {
+                    Some(tiles) if tiles == open => TileSet::EMPTY,
+                    Some(tiles) => {
+                        let next = (mask ^ tiles.0 as usize) * total;
+                        for (rolls, &val) in row.iter().enumerate().take(total - 1) {
+                            lower[next + rolls + 1] += val * prob;
+                        }
+                        continue;
+                    }
+                    None => open,
+                };
+                // The game ends on this roll with `end` open
+                out.win += end.is_empty() as u8 as f64 * reach * prob;
+                for tile in full.difference(end) {
+                    out.shut[tile - 1] += reach * prob;
+                }
+                for (rolls, &val) in row.iter().enumerate() {
+                    out.nrolls[rolls] += val * prob;
+                }
+                out.lastroll[roll - 1] += reach * prob;
+            }
+        }
+        out
+    }
+
+    /// Probability that the gap between two consecutive wins is `lo` to `hi` games
+    pub fn win_gap(&self, lo: u64, hi: u64) -> f64 {
+        let miss = 1.0 - self.win;
+        let at_least = |games: u64| miss.powf(games.max(1) as f64 - 1.0);
+        at_least(lo) - at_least(hi.saturating_add(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::DiceConfig;
+    use crate::game::simulate_batch;
+    use crate::rules::OneDie;
+    use crate::strategy::{Greedy, StrategyKind};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_exact_two_tiles() {
+        let exact = Outcomes::new(&Rules::new(2), &mut Greedy);
+        // Rolling 3 wins at once, rolling 2 shuts the 2 and strands the 1
+        assert!(close(exact.win, 2.0 / 36.0));
+        assert!(close(exact.shut[0], 2.0 / 36.0));
+        assert!(close(exact.shut[1], 3.0 / 36.0));
+        assert!(close(exact.nrolls[0], 35.0 / 36.0));
+        assert!(close(exact.nrolls[1], 1.0 / 36.0));
+        assert!(close(exact.lastroll[6], 6.0 / 36.0 + 6.0 / 36.0 / 36.0));
+        assert!(close(exact.rawrolls.iter().sum(), 37.0 / 36.0));
+        assert!(close(exact.win_gap(1, 1), 2.0 / 36.0));
+        assert!(close(exact.win_gap(1, u64::MAX), 1.0));
+    }
+
+    #[test]
+    fn test_exact_matches_simulation() {
+        let mut rules = Rules::new(9);
+        rules.one_die = OneDie::May;
+        let exact = Outcomes::new(&rules, &mut Greedy);
+        assert!(close(exact.nrolls.iter().sum(), 1.0));
+        assert!(close(exact.lastroll.iter().sum(), 1.0));
+
+        let games = 20_000;
+        let stats = simulate_batch(games, 5, StrategyKind::Greedy, &rules);
+        // Allow five standard errors of a sampled proportion
+        let check = |prob: f64, count: u64| {
+            let error = 5.0 * (prob * (1.0 - prob) / games as f64).sqrt() + 1e-9;
+            assert!((count as f64 / games as f64 - prob).abs() < error);
+        };
+        check(exact.win, stats.num_won);
+        for (&prob, &count) in exact.shut.iter().zip(stats.count_shut.iter()) {
+            check(prob, count);
+        }
+        for (&prob, &count) in exact.nrolls.iter().zip(stats.count_nrolls.iter()) {
+            check(prob, count);
+        }
+        for (&prob, &count) in exact.lastroll.iter().zip(stats.count_lastroll.iter()) {
+            check(prob, count);
+        }
+    }
+
+    #[test]
+    fn test_exact_loaded_dice() {
+        let mut rules = Rules::new(8);
+        rules.dice = DiceConfig {
+            weights: Some(vec![1.0, 1.0, 1.0, 2.0]),
+            ..DiceConfig::fair(2, 4)
+        };
+        let exact = Outcomes::new(&rules, &mut Greedy);
+        assert_eq!(exact.rawrolls.len(), 8);
+        assert_eq!(exact.rawrolls[0], 0.0);
+        let games = 20_000;
+        let stats = simulate_batch(games, 5, StrategyKind::Greedy, &rules);
+        let error = 5.0 * (exact.win * (1.0 - exact.win) / games as f64).sqrt();
+        assert!((stats.num_won as f64 / games as f64 - exact.win).abs() < error);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::game::Statistics;
+use crate::score::ScoreRule;
+use std::fmt::Write;
+
+/// Percentiles reported for histograms in JSON and CSV exports
+const PERCENTILES: [f64; 7] = [10.0, 25.0, 50.0, 75.0, 90.0, 99.0, 99.9];
+
+/// Serialize a `Histogram` as its recorded (value, count) pairs plus summary percentiles
+pub mod histogram {
+    use super::PERCENTILES;
+    use hdrhistogram::Histogram;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Record {
+        sigfig: u8,
+        counts: Vec<(u64, u64)>,
+        // Derived values, only written for readers of the file
+        #[serde(default, skip_deserializing)]
+        mean: f64,
+        #[serde(default, skip_deserializing)]
+        max: u64,
+        #[serde(default, skip_deserializing)]
+        percentiles: Vec<(f64, u64)>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        hist: &Histogram<u64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Record {
+            sigfig: hist.sigfig(),
+            counts: hist
+                .iter_recorded()
+                .map(|x| (x.value_iterated_to(), x.count_at_value()))
+                .collect(),
+            mean: hist.mean(),
+            max: hist.max(),
+            percentiles: PERCENTILES
+                .iter()
+                .map(|&p| (p, hist.value_at_percentile(p)))
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Histogram<u64>, D::Error> {
+        let record = Record::deserialize(deserializer)?;
+        let mut hist = Histogram::new(record.sigfig)
+            .map_err(|err| Error::custom(format!("Invalid histogram: {:?}", err)))?;
+        for (value, count) in record.counts {
+            hist.record_n(value, count)
+                .map_err(|err| Error::custom(format!("Invalid histogram count: {:?}", err)))?;
+        }
+        Ok(hist)
+    }
+}
+
+/// Serialize per-rule score histograms as a map from score rule name to histogram
+pub mod scores {
+    use crate::game::score_histograms;
+    use crate::score::ScoreRule;
+    use hdrhistogram::Histogram;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    struct Borrowed<'a>(&'a Histogram<u64>);
+
+    impl Serialize for Borrowed<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::histogram::serialize(self.0, serializer)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Owned(#[serde(with = "super::histogram")] Histogram<u64>);
+
+    pub fn serialize<S: Serializer>(
+        hists: &[Histogram<u64>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            ScoreRule::ALL
+                .iter()
+                .zip(hists.iter())
+                .map(|(rule, hist)| (rule.name(), Borrowed(hist))),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Histogram<u64>>, D::Error> {
+        let mut map: HashMap<String, Owned> = HashMap::deserialize(deserializer)?;
+        let mut hists = score_histograms();
+        for (rule, hist) in ScoreRule::ALL.iter().zip(hists.iter_mut()) {
+            if let Some(Owned(found)) = map.remove(rule.name()) {
+                *hist = found;
+            }
+        }
+        Ok(hists)
+    }
+}
+
+fn counts_csv(header: &str, counts: &[u64]) -> String {
+    let mut out = format!("{},count\n", header);
+    for (ii, count) in counts.iter().enumerate() {
+        writeln!(out, "{},{}", ii + 1, count).unwrap();
+    }
+    out
+}
+
+impl Statistics {
+    /// Serialize to pretty-printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Load statistics previously written by `to_json`
+    pub fn from_json(text: &str) -> serde_json::Result<Statistics> {
+        serde_json::from_str(text)
+    }
+
+    /// One CSV table per statistic, as (table name, CSV contents)
+    pub fn csv_tables(&self) -> Vec<(&'static str, String)> {
+        let hist = &self.games_between_win;
+        let mut wins = String::from("games,count\n");
+        for x in hist.iter_recorded() {
+            writeln!(wins, "{},{}", x.value_iterated_to(), x.count_at_value()).unwrap();
+        }
+        let mut percentiles = String::from("percentile,games\n");
+        for &p in PERCENTILES.iter() {
+            writeln!(percentiles, "{},{}", p, hist.value_at_percentile(p)).unwrap();
+        }
+        let mut scores = String::from("rule,score,count\n");
+        for &rule in ScoreRule::ALL.iter() {
+            for x in self.score_histogram(rule).iter_recorded() {
+                let (score, count) = (x.value_iterated_to(), x.count_at_value());
+                writeln!(scores, "{},{},{}", rule.name(), score, count).unwrap();
+            }
+        }
+        vec![
+            (
+                "summary",
+                format!(
+                    "num_won,num_total,last_won\n{},{},{}\n",
+                    self.num_won, self.num_total, self.last_won
+                ),
+            ),
+            ("count_shut", counts_csv("box", &self.count_shut)),
+            ("count_rawrolls", counts_csv("roll", &self.count_rawrolls)),
+            ("count_nrolls", counts_csv("rolls", &self.count_nrolls)),
+            ("count_lastroll", counts_csv("roll", &self.count_lastroll)),
+            ("count_ndice", counts_csv("dice", &self.count_ndice)),
+            (
+                "count_move_tiles",
+                counts_csv("tiles", &self.count_move_tiles),
+            ),
+            ("games_between_win", wins),
+            ("games_between_win_percentiles", percentiles),
+            ("scores", scores),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{simulate_batch, Statistics};
+    use crate::rules::Rules;
+    use crate::strategy::StrategyKind;
+
+    #[test]
+    fn test_json_roundtrip() {
+        let stats = simulate_batch(5000, 3, StrategyKind::Greedy, &Rules::new(12));
+        let text = stats.to_json().unwrap();
+        assert!(text.contains("percentiles"));
+        let loaded = Statistics::from_json(&text).unwrap();
+        assert_eq!(loaded.num_won, stats.num_won);
+        assert_eq!(loaded.num_total, stats.num_total);
+        assert_eq!(loaded.last_won, stats.last_won);
+        assert_eq!(loaded.count_shut, stats.count_shut);
+        assert_eq!(loaded.count_rawrolls, stats.count_rawrolls);
+        assert_eq!(loaded.count_nrolls, stats.count_nrolls);
+        assert_eq!(loaded.count_lastroll, stats.count_lastroll);
+        assert_eq!(loaded.count_ndice, stats.count_ndice);
+        assert!(loaded.games_between_win == stats.games_between_win);
+        assert!(loaded.scores == stats.scores);
+        assert_eq!(loaded.golf_card, stats.golf_card);
+    }
+
+    #[test]
+    fn test_csv_tables() {
+        let stats = simulate_batch(100, 3, StrategyKind::Greedy, &Rules::new(12));
+        let tables = stats.csv_tables();
+        let (name, shut) = &tables[1];
+        assert_eq!(*name, "count_shut");
+        assert!(shut.starts_with("box,count\n1,"));
+        assert_eq!(shut.lines().count(), stats.count_shut.len() + 1);
+        let (name, scores) = tables.last().unwrap();
+        assert_eq!(*name, "scores");
+        assert!(scores.contains("\ndigits,"));
+    }
+}
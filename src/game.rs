@@ -1,118 +1,373 @@
+use crate::dice::{Dice, Faces};
+use crate::error::{Error, GameError};
+use crate::interval::{Interval, IntervalMethod, CONFIDENCE};
+use crate::rules::Rules;
+use crate::score::{GolfCard, ScoreRule, GOLF_GAMES};
+use crate::strategy::{Greedy, Strategy, StrategyKind};
+use crate::tiles::{subsets, Subsets, TileSet, MAX_TILES};
 use hdrhistogram::Histogram;
-use rand::Rng;
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
-pub fn simulate_game() -> ShutTheBox {
-    let mut game = ShutTheBox::init(12);
-    let mut dice = Dice::new();
+/// Number of consecutive games simulated together by one batch worker
+/// A multiple of GOLF_GAMES, so golf rounds never straddle two blocks
+const BATCH_BLOCK: u64 = 455 * GOLF_GAMES;
+
+/// Number of tiles on a standard box
+pub const DEFAULT_TILES: usize = 12;
+
+pub fn simulate_game(strategy: &mut dyn Strategy, dice: &mut Dice, rules: &Rules) -> ShutTheBox {
+    let mut game = ShutTheBox::with_rules(rules);
     let mut valid = true;
     while valid && !game.victory() {
-        dice.roll();
-        valid = game.play_roll(dice.result());
+        rules.roll(dice, game.open_tiles(), strategy);
+        valid = game.play_dice(dice, strategy);
     }
     game
 }
 
-#[derive(Debug)]
+/// Derive the seed for one game of a batch (splitmix64 finalizer)
+pub fn game_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Simulate a single game with dice (and strategy) seeded from `seed`
+pub fn simulate_seeded(strategy: &mut dyn Strategy, seed: u64, rules: &Rules) -> ShutTheBox {
+    strategy.reseed(!seed);
+    let mut dice = Dice::with_seed(seed).with_config(&rules.dice);
+    simulate_game(strategy, &mut dice, rules)
+}
+
+/// Simulate `games` seeded games on the current rayon pool
+/// Game N always uses `game_seed(seed, N)` and blocks of games are merged in order,
+/// so the result does not depend on the number of threads
+pub fn simulate_batch(games: u64, seed: u64, strategy: StrategyKind, rules: &Rules) -> Statistics {
+    simulate_range(0, games, seed, strategy, rules)
+}
+
+/// Simulate seeded games `start..end` like `simulate_batch`
+/// With `start` a multiple of `BATCH_BLOCK`, merging consecutive ranges in order gives
+/// the same statistics as one batch
+fn simulate_range(
+    start: u64,
+    end: u64,
+    seed: u64,
+    strategy: StrategyKind,
+    rules: &Rules,
+) -> Statistics {
+    let blocks: Vec<Statistics> = (start / BATCH_BLOCK..end.div_ceil(BATCH_BLOCK))
+        .into_par_iter()
+        .map_init(
+            || strategy.build(rules),
+            |strategy, block| {
+                let mut stats = Statistics::for_rules(rules);
+                let first = start.max(block * BATCH_BLOCK);
+                let last = end.min((block + 1) * BATCH_BLOCK);
+                for ii in first..last {
+                    let game = simulate_seeded(strategy.as_mut(), game_seed(seed, ii), rules);
+                    stats.save_game(&game);
+                }
+                stats
+            },
+        )
+        .collect();
+    let mut stats = Statistics::for_rules(rules);
+    for block in blocks.iter() {
+        stats.merge(block);
+    }
+    stats
+}
+
+/// Simulate seeded games until the win rate interval is within +/- `precision`,
+/// or `max_games` games have been played
+/// Games are played in batches sized from the current interval, so a few more games
+/// than strictly needed may be played.
+pub fn simulate_until(
+    precision: f64,
+    max_games: u64,
+    seed: u64,
+    strategy: StrategyKind,
+    rules: &Rules,
+) -> Statistics {
+    let mut stats = Statistics::for_rules(rules);
+    let mut end = BATCH_BLOCK;
+    while stats.num_total < max_games {
+        let batch = simulate_range(stats.num_total, end.min(max_games), seed, strategy, rules);
+        stats.merge(&batch);
+        let width = stats.win_interval(IntervalMethod::Wilson).half_width();
+        if width <= precision {
+            break;
+        }
+        // The interval shrinks with the square root of the number of games
+        let needed = stats.num_total as f64 * (width / precision).powi(2);
+        end = (needed as u64)
+            .div_ceil(BATCH_BLOCK)
+            .max(end / BATCH_BLOCK + 1)
+            * BATCH_BLOCK;
+    }
+    stats
+}
+
+fn merge_counts(counts: &mut Vec<u64>, other: &[u64]) {
+    if counts.len() < other.len() {
+        counts.resize(other.len(), 0);
+    }
+    for (count, val) in counts.iter_mut().zip(other.iter()) {
+        *count += val;
+    }
+}
+
+/// One empty score histogram per score rule, indexed like `ScoreRule::ALL`
+pub(crate) fn score_histograms() -> Vec<Histogram<u64>> {
+    ScoreRule::ALL
+        .iter()
+        .map(|_| Histogram::<u64>::new(4).unwrap())
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Statistics {
+    /// Rules the games were played with
+    pub rules: Rules,
     pub num_won: u64,
     pub num_total: u64,
     pub last_won: u64,
+    #[serde(default)]
+    pub first_won: Option<u64>,
+    #[serde(with = "crate::export::histogram")]
     pub games_between_win: Histogram<u64>,
     pub count_shut: Vec<u64>,
     pub count_rawrolls: Vec<u64>,
     pub count_nrolls: Vec<u64>,
     pub count_lastroll: Vec<u64>,
+    #[serde(default)]
+    pub count_ndice: Vec<u64>,
+    pub count_move_tiles: Vec<u64>,
+    #[serde(with = "crate::export::scores")]
+    pub scores: Vec<Histogram<u64>>,
+    pub golf_card: GolfCard,
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self::new(DEFAULT_TILES)
+    }
 }
 
 impl Statistics {
-    pub fn new() -> Statistics {
-        let mut default_hash = HashMap::with_capacity(12);
-        for ii in 0..12 {
-            default_hash.insert(ii, 0.0);
-        }
+    /// Empty statistics for games played on a board of `total` tiles with two dice
+    pub fn new(total: usize) -> Statistics {
+        Statistics::for_rules(&Rules::new(total))
+    }
+
+    /// Empty statistics for games played with these rules
+    pub fn for_rules(rules: &Rules) -> Statistics {
+        let total = rules.tiles;
+        let max_roll = rules.dice.max_sum();
         Statistics {
+            rules: rules.clone(),
             num_won: 0,
             num_total: 0,
             last_won: 0,
+            first_won: None,
             games_between_win: Histogram::<u64>::new(4).unwrap(),
-            count_shut: vec![0; 12],
-            count_rawrolls: vec![0; 12],
-            count_nrolls: vec![0; 12],
-            count_lastroll: vec![0; 12],
+            count_shut: vec![0; total],
+            count_rawrolls: vec![0; max_roll],
+            // A game takes at most one roll per tile (the last one shuts or loses)
+            count_nrolls: vec![0; total],
+            count_lastroll: vec![0; max_roll],
+            // Rolls thrown with one die, two dice and so on
+            count_ndice: vec![0; rules.dice.count.max(2)],
+            // Rolls that shut one tile, two tiles and so on
+            count_move_tiles: Vec::new(),
+            scores: score_histograms(),
+            golf_card: GolfCard::default(),
         }
     }
 
+    /// Confidence interval for the win rate
+    pub fn win_interval(&self, method: IntervalMethod) -> Interval {
+        method.interval(self.num_won, self.num_total, CONFIDENCE)
+    }
+
+    /// Confidence interval for the probability that `tile` is shut at the end of a game
+    pub fn shut_interval(&self, tile: usize, method: IntervalMethod) -> Interval {
+        let shut = tile
+            .checked_sub(1)
+            .and_then(|ii| self.count_shut.get(ii))
+            .copied()
+            .unwrap_or(0);
+        method.interval(shut, self.num_total, CONFIDENCE)
+    }
+
+    /// Histogram of game scores under a score rule (of whole rounds for golf)
+    pub fn score_histogram(&self, rule: ScoreRule) -> &Histogram<u64> {
+        &self.scores[rule.index()]
+    }
+
+    /// Save a finished game, panicking if it cannot be counted (see `try_save_game`)
     pub fn save_game(&mut self, game: &ShutTheBox) {
+        self.try_save_game(game).expect("Could not save game");
+    }
+
+    /// Save a finished game, or leave the statistics unchanged if it cannot be counted
+    pub fn try_save_game(&mut self, game: &ShutTheBox) -> Result<(), GameError> {
+        if game.total != self.count_shut.len() {
+            return Err(GameError::BoardSize {
+                expected: self.count_shut.len(),
+                found: game.total,
+            });
+        }
+        if game.rolls.contains(&0) {
+            return Err(GameError::RollOutOfRange(0));
+        }
+        let nrolls = game.rolls.len();
+        if nrolls == 0 {
+            return Err(GameError::NoRoll);
+        }
+        let max_roll = game.rolls.iter().copied().max().unwrap_or(0);
+        if self.count_rawrolls.len() < max_roll {
+            // Only reachable when the game used larger dice than the statistics expected
+            self.count_rawrolls.resize(max_roll, 0);
+            self.count_lastroll.resize(max_roll, 0);
+        }
+        if self.count_nrolls.len() < nrolls {
+            // Only reachable when tiles were reopened during the game
+            self.count_nrolls.resize(nrolls, 0);
+        }
         self.num_won += game.victory() as u64;
         if game.victory() {
             self.games_between_win
-                .record((self.num_total - self.last_won) as u64)
+                .record(self.num_total - self.last_won)
                 .expect("Could not add value to histogram");
             self.last_won = self.num_total;
+            self.first_won.get_or_insert(self.num_total);
         }
         self.num_total += 1;
-        for (ii, shut) in game.status.iter().enumerate() {
-            self.count_shut[ii] += *shut as u64;
+        for tile in game.iter_shut() {
+            self.count_shut[tile - 1] += 1;
         }
         for roll in game.rolls.iter() {
             self.count_rawrolls[roll - 1] += 1;
         }
+        for ndice in game.moves.iter().map(Move::count).filter(|&x| x > 0) {
+            if self.count_ndice.len() < ndice {
+                self.count_ndice.resize(ndice, 0);
+            }
+            self.count_ndice[ndice - 1] += 1;
+        }
+        for ntiles in game.moves.iter().map(|x| x.tiles.len()).filter(|&x| x > 0) {
+            if self.count_move_tiles.len() < ntiles {
+                self.count_move_tiles.resize(ntiles, 0);
+            }
+            self.count_move_tiles[ntiles - 1] += 1;
+        }
         // Save Rolls
-        let rolls = game.get_rolls();
-        let nrolls = rolls.len();
         self.count_nrolls[nrolls - 1] += 1;
-        self.count_lastroll[rolls[nrolls - 1] - 1] += 1;
+        self.count_lastroll[game.rolls[nrolls - 1] - 1] += 1;
+        for &rule in ScoreRule::ALL.iter() {
+            let score = match rule {
+                ScoreRule::Golf => match self.golf_card.add(game.score(rule)) {
+                    Some(total) => total,
+                    None => continue,
+                },
+                _ => game.score(rule),
+            };
+            self.scores[rule.index()]
+                .record(score)
+                .expect("Could not add value to histogram");
+        }
+        Ok(())
     }
-}
-
-struct VecTotal {
-    values: Vec<usize>,
-    total: usize,
-}
 
-impl VecTotal {
-    fn push(&mut self, val: usize) {
-        self.total += val;
-        self.values.push(val)
+    /// Add the games in `other` like `merge`, if they were played with the same rules
+    pub fn try_merge(&mut self, other: &Statistics) -> Result<(), Error> {
+        if other.rules != self.rules {
+            return Err(Error::Merge(format!(
+                "games played with {} and with {}",
+                self.rules, other.rules
+            )));
+        }
+        if other.count_shut.len() != self.count_shut.len() {
+            return Err(Error::Merge(format!(
+                "counts for {} tiles and for {}",
+                self.count_shut.len(),
+                other.count_shut.len()
+            )));
+        }
+        self.merge(other);
+        Ok(())
     }
-    fn pop(&mut self) -> usize {
-        match self.values.pop() {
-            Some(val) => {
-                self.total -= val;
-                val
-            }
-            None => 0,
+
+    /// Add the games in `other`, as if they were played after the games already saved
+    pub fn merge(&mut self, other: &Statistics) {
+        let mut between = other.games_between_win.clone();
+        if let Some(first) = other.first_won {
+            // The first win in other was counted from its own start, not from our last win
+            let mut seam = Histogram::<u64>::new(between.sigfig()).unwrap();
+            seam.record(first)
+                .expect("Could not add value to histogram");
+            between
+                .subtract(&seam)
+                .expect("Could not remove value from histogram");
+            between
+                .record(self.num_total - self.last_won + first)
+                .expect("Could not add value to histogram");
+            self.first_won.get_or_insert(self.num_total + first);
+            self.last_won = self.num_total + other.last_won;
+        }
+        self.games_between_win
+            .add(&between)
+            .expect("Could not merge histograms");
+        self.num_won += other.num_won;
+        self.num_total += other.num_total;
+        merge_counts(&mut self.count_shut, &other.count_shut);
+        merge_counts(&mut self.count_rawrolls, &other.count_rawrolls);
+        merge_counts(&mut self.count_nrolls, &other.count_nrolls);
+        merge_counts(&mut self.count_lastroll, &other.count_lastroll);
+        merge_counts(&mut self.count_ndice, &other.count_ndice);
+        merge_counts(&mut self.count_move_tiles, &other.count_move_tiles);
+        for (hist, other_hist) in self.scores.iter_mut().zip(other.scores.iter()) {
+            hist.add(other_hist).expect("Could not merge histograms");
+        }
+        // Golf rounds only line up if our last round was complete, otherwise it is dropped
+        if other.num_total > 0 {
+            self.golf_card = other.golf_card;
         }
     }
 }
 
-fn check_slice(sumvec: &mut VecTotal, slice: &[usize], target: usize) -> bool {
-    if slice.len() == 0 {
-        return false;
-    }
-    for ii in 0..slice.len() {
-        sumvec.push(slice[ii]);
-        let hit = match sumvec.total.cmp(&target) {
-            Ordering::Less => check_slice(sumvec, &slice[ii + 1..], target),
-            Ordering::Greater => false,
-            Ordering::Equal => true,
-        };
-        if hit {
-            return true;
-        }
-        sumvec.pop();
+/// One roll of a game and the tiles it shut
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    /// Value of each die thrown, empty if only the sum was recorded
+    pub dice: Faces,
+    pub sum: usize,
+    /// Tiles shut, empty if the roll could not be played
+    pub tiles: TileSet,
+    /// Tiles open before the roll, not saved since it follows from the earlier moves
+    #[serde(skip)]
+    pub board_before: TileSet,
+}
+
+impl Move {
+    /// Number of dice thrown, or 0 if only the sum was recorded
+    pub fn count(&self) -> usize {
+        self.dice.len()
     }
-    false
 }
 
 /// ShutTheBox struct represents the game board
 #[derive(Debug)]
 pub struct ShutTheBox {
-    status: Vec<bool>, // Indicate TRUE if SHUT
+    open: TileSet,     // Tiles that are still open
     rolls: Vec<usize>, // Ordered Roll History
     shut: Vec<usize>,  // Ordered Number History
+    moves: Vec<Move>,  // Roll History with the tiles shut by each roll
+    max_shut: usize,   // Most tiles one roll may shut
     pub total: usize,  // Number of boxes
 }
 
@@ -120,112 +375,176 @@ impl ShutTheBox {
     /// Initialize ShutTheBox with a max size
     pub fn init(total: usize) -> ShutTheBox {
         ShutTheBox {
-            status: vec![false; total],
+            open: TileSet::full(total),
             rolls: Vec::with_capacity(total),
             shut: Vec::with_capacity(total),
-            total: total,
+            moves: Vec::with_capacity(total),
+            max_shut: MAX_TILES,
+            total,
+        }
+    }
+
+    /// Initialize an empty board for a game played with `rules`
+    pub fn with_rules(rules: &Rules) -> ShutTheBox {
+        ShutTheBox {
+            max_shut: rules.shut_limit(),
+            ..ShutTheBox::init(rules.tiles)
         }
     }
 
     pub fn get_status(&self, val: usize) -> Option<bool> {
         // 1-indexed... cannot be 0 or > capacity
-        if val == 0 || val > self.status.len() {
+        if val == 0 || val > self.total {
             return None;
         }
-        Some(self.status[val - 1])
+        Some(!self.open.contains(val))
     }
 
+    /// Shut a tile, ignoring tiles that are not open (see `try_shut`)
     pub fn shut(&mut self, val: usize) {
-        if val > 0 && val < self.status.len() + 1 {
-            self.shut.push(val);
-            self.status[val - 1] = true;
+        let _ = self.try_shut(val);
+    }
+
+    /// Shut an open tile, adding it to the last roll
+    pub fn try_shut(&mut self, val: usize) -> Result<(), GameError> {
+        match self.get_status(val) {
+            None => return Err(GameError::TileOutOfRange(val)),
+            Some(true) => return Err(GameError::AlreadyShut(val)),
+            Some(false) => {}
+        }
+        self.shut.push(val);
+        self.open = self.open.difference(TileSet::single(val));
+        if let Some(last) = self.moves.last_mut() {
+            last.tiles = last.tiles.union(TileSet::single(val));
+        }
+        Ok(())
+    }
+
+    /// Play the last roll by shutting `tiles`, checking that the move is legal
+    pub fn try_play(&mut self, tiles: TileSet) -> Result<(), GameError> {
+        let last = self.moves.last().ok_or(GameError::NoRoll)?;
+        if !last.tiles.is_empty() {
+            return Err(GameError::AlreadyPlayed);
+        }
+        if let Some(tile) = tiles.iter().find(|&x| x > self.total) {
+            return Err(GameError::TileOutOfRange(tile));
+        }
+        if let Some(tile) = tiles.difference(self.open).iter().next() {
+            return Err(GameError::AlreadyShut(tile));
+        }
+        if tiles.len() > self.max_shut {
+            return Err(GameError::TooManyTiles {
+                max: self.max_shut,
+                found: tiles.len(),
+            });
+        }
+        if tiles.sum() != last.sum {
+            return Err(GameError::WrongSum {
+                roll: last.sum,
+                sum: tiles.sum(),
+            });
+        }
+        self.shut_tiles(tiles);
+        Ok(())
+    }
+
+    /// Reopen a shut tile, removing it from the history of shut tiles
+    /// Later moves keep their tiles but now see the tile open before them
+    pub fn unshut(&mut self, val: usize) {
+        if self.get_status(val) != Some(true) {
+            return;
+        }
+        if let Some(idx) = self.shut.iter().rposition(|&x| x == val) {
+            self.shut.remove(idx);
+        }
+        if let Some(idx) = self.moves.iter().rposition(|x| x.tiles.contains(val)) {
+            self.moves[idx].tiles = self.moves[idx].tiles.difference(TileSet::single(val));
+            for later in self.moves[idx + 1..].iter_mut() {
+                later.board_before = later.board_before.union(TileSet::single(val));
+            }
+        }
+        self.open = self.open.union(TileSet::single(val));
+    }
+
+    /// Shut every tile in the set, highest first
+    pub fn shut_tiles(&mut self, tiles: TileSet) {
+        for val in (1..self.total + 1).rev().filter(|x| tiles.contains(*x)) {
+            self.shut(val);
         }
     }
 
     /// Check for victory
     pub fn victory(&self) -> bool {
-        self.status.iter().all(|&x| x)
+        self.open.is_empty()
     }
 
     /// Check for loss, given a particular value and game state
     pub fn check_loss(&self, target: usize) -> bool {
-        let open: Vec<usize> = self.iter_open().filter(|x| *x <= target).collect();
-        let mut sumvec = VecTotal {
-            values: Vec::new(),
-            total: 0,
-        };
-        let alive = check_slice(&mut sumvec, &open[0..], target);
-        // if alive {
-        //     println!("Valid Open: {:?}", sumvec.values);
-        // }
-        !alive
+        self.legal_moves(target).next().is_none()
     }
 
-    /// Attempt to play a roll
+    /// Every set of open tiles that sums to `roll` and that the rules allow one roll
+    /// to shut, in greedy preference order
+    /// Use `at_most` on the result to cap the number of tiles in a move further
+    pub fn legal_moves(&self, roll: usize) -> Subsets {
+        subsets(self.open, roll).at_most(self.max_shut)
+    }
+
+    /// Attempt to play a roll using the default greedy strategy
     /// Returns true if the roll was played successfully...
     /// Returns false if the game is OVER
     pub fn play_roll(&mut self, roll: usize) -> bool {
-        // Save the roll
-        self.rolls.push(roll);
-
-        // Check if open
-        let open = match self.get_status(roll) {
-            Some(false) => true,
-            Some(true) => false,
-            None => false,
-        };
+        self.play_roll_with(roll, &mut Greedy)
+    }
 
-        // Play the roll if its open
-        if open {
-            self.shut(roll);
-            return true;
-        } else {
-            // Try to split the roll if otherwise
-            for ii in 1..(roll as f32 / 2.0).ceil() as usize {
-                let closed_high = self.get_status(roll - ii).unwrap();
-                let closed_low = self.get_status(ii).unwrap();
-                if !closed_low && !closed_high {
-                    self.shut(roll - ii);
-                    self.shut(ii);
-                    return true;
-                }
-            }
-            // Next try to split the roll N-way
-            let open: Vec<usize> = self.iter_open().filter(|x| *x <= roll).collect();
-            let mut sumvec = VecTotal {
-                values: Vec::new(),
-                total: 0,
-            };
-            let alive = check_slice(&mut sumvec, &open[0..], roll);
-            if alive {
-                for ii in sumvec.values.iter() {
-                    self.shut(*ii);
-                }
-                return true;
-            }
-            return false;
+    /// Attempt to play a roll of the dice, letting the strategy pick which tiles to shut
+    /// An illegal choice ends the game like a roll that cannot be played
+    pub fn play_roll_with(&mut self, roll: usize, strategy: &mut dyn Strategy) -> bool {
+        self.save_roll(roll);
+        self.play_saved(roll, strategy).unwrap_or(false)
+    }
+
+    /// Attempt to play the last roll of `dice`, letting the strategy pick which tiles to shut
+    /// An illegal choice ends the game like a roll that cannot be played
+    pub fn play_dice(&mut self, dice: &Dice, strategy: &mut dyn Strategy) -> bool {
+        self.try_play_dice(dice, strategy).unwrap_or(false)
+    }
+
+    /// Like `play_dice`, but returns the reason the strategy's choice was illegal
+    pub fn try_play_dice(
+        &mut self,
+        dice: &Dice,
+        strategy: &mut dyn Strategy,
+    ) -> Result<bool, GameError> {
+        self.save_dice(dice);
+        self.play_saved(dice.result(), strategy)
+    }
+
+    fn play_saved(&mut self, roll: usize, strategy: &mut dyn Strategy) -> Result<bool, GameError> {
+        match strategy.choose_from(self.open, roll, self.legal_moves(roll)) {
+            Some(tiles) => self.try_play(tiles).map(|_| true),
+            None => Ok(false),
         }
     }
 
+    /// Score of the tiles left open under a score rule
+    pub fn score(&self, rule: ScoreRule) -> u64 {
+        rule.score(self.open)
+    }
+
+    /// Set of tiles that are still open
+    pub fn open_tiles(&self) -> TileSet {
+        self.open
+    }
+
     /// Return ordered vector of numbers that have been shut
     pub fn get_shut(&self) -> Vec<usize> {
-        // Ordered list of numbers that have been shut
-        // (1..self.status.len() + 1)
-        //     .filter(|ii| self.get(*ii).unwrap())
-        //     .collect()
         self.iter_shut().collect()
     }
 
     pub fn iter_shut<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
         // Iterator of numbers that have been shut
-        // (1..self.status.len() + 1)
-        //     .filter(|ii| self.get(*ii).unwrap())
-        //     .collect()
-        self.status
-            .iter()
-            .enumerate()
-            .filter(|shut| *shut.1)
-            .map(|pair| pair.0 + 1)
+        TileSet::full(self.total).difference(self.open).iter()
     }
 
     /// Return ordered vector of numbers that are still open
@@ -235,76 +554,379 @@ impl ShutTheBox {
 
     pub fn iter_open<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
         // Iterator of numbers that are still open
-        self.status
-            .iter()
-            .enumerate()
-            .filter(|shut| !*shut.1)
-            .map(|pair| pair.0 + 1)
+        self.open.iter()
     }
 
+    /// Record a roll of the dice when only their sum is known
     pub fn save_roll(&mut self, roll: usize) {
-        self.rolls.push(roll);
+        self.save_move(&[], roll);
+    }
+
+    /// Record the last roll of `dice`
+    pub fn save_dice(&mut self, dice: &Dice) {
+        self.save_move(&dice.values, dice.result());
+    }
+
+    /// Record a roll with these dice values
+    pub fn save_move(&mut self, dice: &[usize], sum: usize) {
+        self.rolls.push(sum);
+        self.moves.push(Move {
+            dice: Faces::new(dice),
+            sum,
+            tiles: TileSet::EMPTY,
+            board_before: self.open,
+        });
+    }
+
+    /// Remove the last roll from the history, returning it
+    pub fn pop_roll(&mut self) -> Option<usize> {
+        self.moves.pop();
+        self.rolls.pop()
+    }
+
+    /// Return every roll with the tiles it shut, in the order they were played
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
     }
 
     /// Return vector of rolls, in the order they were played
-    pub fn get_rolls<'a>(&'a self) -> &'a Vec<usize> {
+    pub fn get_rolls(&self) -> &Vec<usize> {
         &self.rolls
     }
 
+    /// Return the number of dice thrown for each roll
+    pub fn get_dice_counts(&self) -> Vec<usize> {
+        self.moves.iter().map(Move::count).collect()
+    }
+
     // Return vector of numbers, in the order they were played
-    pub fn get_numbers<'a>(&'a self) -> &'a Vec<usize> {
+    pub fn get_numbers(&self) -> &Vec<usize> {
         &self.shut
     }
 }
 
-/// Struct for handling a 2-Dice roll
-pub struct Dice {
-    rng: rand::rngs::ThreadRng,
-    pub values: (usize, usize),
-}
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::ShutTheBox;
+    use super::{game_seed, simulate_batch, simulate_seeded, simulate_until, Statistics};
+    use super::{IntervalMethod, BATCH_BLOCK};
+    use crate::dice::{Dice, DiceConfig};
+    use crate::error::GameError;
+    use crate::rules::{OneDie, Rules};
+    use crate::score::{ScoreRule, GOLF_GAMES};
+    use crate::strategy::{Greedy, StrategyKind};
+    use crate::tiles::TileSet;
+
+    #[test]
+    fn test_simulate_seeded() {
+        for &kind in StrategyKind::ALL.iter() {
+            let rules = Rules::new(12);
+            let game1 = simulate_seeded(kind.build(&rules).as_mut(), 7, &rules);
+            let game2 = simulate_seeded(kind.build(&rules).as_mut(), 7, &rules);
+            assert_eq!(game1.get_rolls(), game2.get_rolls());
+            assert_eq!(game1.get_numbers(), game2.get_numbers());
+        }
+    }
 
-impl Dice {
-    pub fn new() -> Dice {
-        Dice {
-            rng: rand::thread_rng(),
-            values: (0, 0),
+    #[test]
+    fn test_simulate_until() {
+        let rules = Rules::new(9);
+        let stats = simulate_until(0.01, 1_000_000, 3, StrategyKind::Greedy, &rules);
+        let interval = stats.win_interval(IntervalMethod::Wilson);
+        assert!(interval.half_width() <= 0.01);
+        assert!(stats.num_total < 1_000_000);
+        assert_eq!(stats.num_total % BATCH_BLOCK, 0);
+        // Batches continue the game sequence of a single run
+        let batch = simulate_batch(stats.num_total, 3, StrategyKind::Greedy, &rules);
+        assert_eq!(stats.num_won, batch.num_won);
+        assert_eq!(stats.count_shut, batch.count_shut);
+        let stats = simulate_until(1e-9, 5000, 3, StrategyKind::Greedy, &rules);
+        assert_eq!(stats.num_total, 5000);
+        for tile in 1..10 {
+            let shut = stats.shut_interval(tile, IntervalMethod::ClopperPearson);
+            assert!(shut.contains(stats.count_shut[tile - 1] as f64 / 5000.0));
+        }
+        // Tiles off the board are never shut
+        for tile in [0, 10] {
+            let shut = stats.shut_interval(tile, IntervalMethod::ClopperPearson);
+            assert_eq!(shut.lo, 0.0);
         }
     }
 
-    pub fn roll(&mut self) {
-        self.values = (self.rng.gen_range(1, 7), self.rng.gen_range(1, 7));
+    #[test]
+    fn test_statistics_merge() {
+        let mut rules = Rules::new(12);
+        rules.one_die = OneDie::May;
+        let mut strategy = StrategyKind::Greedy.build(&rules);
+        let games: Vec<_> = (0..3000)
+            .map(|ii| simulate_seeded(strategy.as_mut(), game_seed(5, ii), &rules))
+            .collect();
+        let mut expected = Statistics::for_rules(&rules);
+        for game in games.iter() {
+            expected.save_game(game);
+        }
+        let mut merged = Statistics::for_rules(&rules);
+        // Whole golf rounds per chunk, so golf scores merge exactly
+        for chunk in games.chunks(70 * 9) {
+            let mut part = Statistics::for_rules(&rules);
+            for game in chunk {
+                part.save_game(game);
+            }
+            merged.try_merge(&part).unwrap();
+        }
+        assert!(merged.try_merge(&Statistics::new(12)).is_err());
+        let mut short = Statistics::for_rules(&rules);
+        short.count_shut.pop();
+        assert!(merged.try_merge(&short).is_err());
+        assert!(expected.num_won > 2);
+        assert_eq!(merged.num_won, expected.num_won);
+        assert_eq!(merged.num_total, expected.num_total);
+        assert_eq!(merged.last_won, expected.last_won);
+        assert_eq!(merged.first_won, expected.first_won);
+        assert_eq!(merged.count_shut, expected.count_shut);
+        assert_eq!(merged.count_rawrolls, expected.count_rawrolls);
+        assert_eq!(merged.count_nrolls, expected.count_nrolls);
+        assert_eq!(merged.count_lastroll, expected.count_lastroll);
+        assert_eq!(merged.count_ndice, expected.count_ndice);
+        assert_eq!(merged.count_move_tiles, expected.count_move_tiles);
+        assert!(merged.scores == expected.scores);
+        assert_eq!(merged.golf_card, expected.golf_card);
+        assert!(merged.games_between_win == expected.games_between_win);
     }
 
-    pub fn result(&self) -> usize {
-        self.values.0 + self.values.1
+    #[test]
+    fn test_simulate_batch_threads() {
+        let run = |threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| simulate_batch(2000, 42, StrategyKind::Random, &Rules::new(12)))
+        };
+        let stats1 = run(1);
+        let stats4 = run(4);
+        assert_eq!(stats1.num_total, 2000);
+        assert_eq!(stats1.num_won, stats4.num_won);
+        assert_eq!(stats1.count_shut, stats4.count_shut);
+        assert_eq!(stats1.count_rawrolls, stats4.count_rawrolls);
+        assert_eq!(stats1.count_nrolls, stats4.count_nrolls);
+        assert_eq!(stats1.count_lastroll, stats4.count_lastroll);
+        assert!(stats1.games_between_win == stats4.games_between_win);
     }
 
-    pub fn pprint(&self) -> String {
-        fn onedie(val: usize) -> &'static str {
-            match val {
-                1 => "⚀",
-                2 => "⚁",
-                3 => "⚂",
-                4 => "⚃",
-                5 => "⚄",
-                6 => "⚅",
-                _ => "?",
-            }
+    #[test]
+    fn test_simulate_board_sizes() {
+        for &total in [1, 9, 10, 14].iter() {
+            let rules = Rules::new(total);
+            let stats = simulate_batch(500, 3, StrategyKind::Optimal, &rules);
+            assert_eq!(stats.num_total, 500);
+            assert_eq!(stats.count_shut.len(), total);
+            assert_eq!(stats.count_nrolls.len(), total);
+            let game = simulate_seeded(StrategyKind::Greedy.build(&rules).as_mut(), 3, &rules);
+            assert_eq!(game.total, total);
+            assert!(game.get_rolls().len() <= total);
         }
-        format!("{} {}", onedie(self.values.0), onedie(self.values.1))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Dice;
-    use super::ShutTheBox;
+    #[test]
+    fn test_simulate_one_die() {
+        let mut rules = Rules::new(9);
+        let stats = simulate_batch(2000, 11, StrategyKind::Greedy, &rules);
+        assert_eq!(stats.count_ndice[0], 0);
+        rules.one_die = OneDie::Must;
+        let stats = simulate_batch(2000, 11, StrategyKind::Greedy, &rules);
+        assert!(stats.count_ndice[0] > 0);
+        let rolls: u64 = stats.count_rawrolls.iter().sum();
+        assert_eq!(stats.count_ndice.iter().sum::<u64>(), rolls);
+        // Only a single die can roll a 1
+        assert!(stats.count_rawrolls[0] > 0);
+        assert!(stats.count_rawrolls[0] <= stats.count_ndice[0]);
+    }
+
+    #[test]
+    fn test_simulate_max_shut() {
+        let mut rules = Rules::new(12);
+        let stats = simulate_batch(2000, 11, StrategyKind::MostTiles, &rules);
+        assert!(stats.count_move_tiles.len() > 2);
+        rules.max_shut = Some(2);
+        for &kind in StrategyKind::ALL.iter() {
+            let stats = simulate_batch(500, 11, kind, &rules);
+            assert_eq!(stats.count_move_tiles.len(), 2, "{}", kind.name());
+        }
+        let mut game = ShutTheBox::with_rules(&rules);
+        assert_eq!(game.legal_moves(6).count(), 3);
+        assert!(game.check_loss(78));
+        game.save_roll(6);
+        assert_eq!(
+            game.try_play(TileSet::from_tiles(&[1, 2, 3])),
+            Err(GameError::TooManyTiles { max: 2, found: 3 })
+        );
+        assert_eq!(game.try_play(TileSet::from_tiles(&[2, 4])), Ok(()));
+    }
 
     #[test]
-    fn test_dice_roll() {
-        let mut dice = Dice::new();
+    fn test_simulate_dice_config() {
+        let mut rules = Rules::new(18);
+        rules.dice = DiceConfig::fair(3, 6);
+        let stats = simulate_batch(2000, 5, StrategyKind::Greedy, &rules);
+        assert_eq!(stats.count_rawrolls.len(), 18);
+        assert_eq!(stats.count_rawrolls[..2], [0, 0]);
+        assert!(stats.count_rawrolls[17] > 0);
+        assert_eq!(stats.count_ndice[..2], [0, 0]);
+        rules = Rules::new(8);
+        rules.dice = DiceConfig::fair(1, 8);
+        let stats = simulate_batch(2000, 5, StrategyKind::Optimal, &rules);
+        assert_eq!(stats.count_rawrolls.len(), 8);
+        assert!(stats.count_rawrolls.iter().all(|&x| x > 0));
+        assert_eq!(
+            stats.count_ndice[0],
+            stats.count_rawrolls.iter().sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_statistics_scores() {
+        let mut stats = Statistics::new(9);
+        let mut game = ShutTheBox::init(9);
+        game.save_roll(12);
+        game.shut_tiles(TileSet::from_tiles(&[3, 9]));
+        for _ in 0..GOLF_GAMES {
+            stats.save_game(&game);
+        }
+        assert_eq!(game.score(ScoreRule::Digits), 1245678);
+        assert_eq!(stats.score_histogram(ScoreRule::Sum).mean(), 33.0);
+        assert_eq!(stats.score_histogram(ScoreRule::Count).max(), 7);
+        let golf = stats.score_histogram(ScoreRule::Golf);
+        assert_eq!(golf.len(), 1);
+        assert_eq!(golf.max(), 33 * GOLF_GAMES);
+        // A partial golf round is dropped when more games are merged after it
+        stats.save_game(&game);
+        let mut merged = Statistics::new(9);
+        merged.merge(&stats);
+        merged.merge(&stats);
+        assert_eq!(merged.score_histogram(ScoreRule::Golf).len(), 2);
+        assert_eq!(merged.golf_card.games, 1);
+    }
+
+    #[test]
+    fn test_play_dice() {
+        let mut dice = Dice::scripted(vec![vec![6, 3], vec![2, 5]]);
+        let mut game = ShutTheBox::init(9);
         dice.roll();
-        assert_eq!(dice.result(), dice.values.0 + dice.values.1);
+        assert!(game.play_dice(&dice, &mut Greedy));
+        dice.roll_one();
+        assert_eq!(dice.count(), 1);
+        assert_eq!(dice.result(), 2);
+        assert!(game.play_dice(&dice, &mut Greedy));
+        game.save_roll(4);
+        assert_eq!(game.get_rolls(), &vec![9, 2, 4]);
+        assert_eq!(game.get_dice_counts(), vec![2, 1, 0]);
+        let moves = game.moves();
+        assert_eq!(moves[0].dice.to_vec(), vec![6, 3]);
+        assert_eq!(moves[0].tiles, TileSet::single(9));
+        assert_eq!(moves[1].dice.to_vec(), vec![2]);
+        assert_eq!(moves[1].board_before, TileSet::full(8));
+        assert_eq!(moves[2].sum, 4);
+        assert!(moves[2].tiles.is_empty());
+    }
+
+    #[test]
+    fn test_unshut_and_pop_roll() {
+        let mut game = ShutTheBox::init(9);
+        game.save_roll(7);
+        game.shut_tiles(TileSet::from_tiles(&[3, 4]));
+        game.save_roll(9);
+        game.shut(9);
+        let tiles: Vec<TileSet> = game.moves().iter().map(|x| x.tiles).collect();
+        assert_eq!(
+            tiles,
+            vec![TileSet::from_tiles(&[3, 4]), TileSet::single(9)]
+        );
+        game.unshut(4);
+        game.unshut(5);
+        assert_eq!(game.get_open(), vec![1, 2, 4, 5, 6, 7, 8]);
+        assert_eq!(game.moves()[0].tiles, TileSet::single(3));
+        assert_eq!(
+            game.moves()[1].board_before,
+            TileSet::full(9).difference(TileSet::single(3))
+        );
+        assert_eq!(game.get_numbers(), &vec![3, 9]);
+        assert_eq!(game.pop_roll(), Some(9));
+        assert_eq!(game.get_rolls(), &vec![7]);
+        assert_eq!(game.get_dice_counts(), vec![0]);
+        assert_eq!(game.moves().len(), 1);
+        game.pop_roll();
+        assert_eq!(game.pop_roll(), None);
+    }
+
+    #[test]
+    fn test_illegal_moves() {
+        let mut game = ShutTheBox::init(9);
+        assert_eq!(game.try_shut(0), Err(GameError::TileOutOfRange(0)));
+        assert_eq!(game.try_shut(10), Err(GameError::TileOutOfRange(10)));
+        assert_eq!(game.try_play(TileSet::single(9)), Err(GameError::NoRoll));
+        game.save_roll(9);
+        assert_eq!(
+            game.try_play(TileSet::from_tiles(&[3, 4])),
+            Err(GameError::WrongSum { roll: 9, sum: 7 })
+        );
+        assert_eq!(game.try_play(TileSet::from_tiles(&[4, 5])), Ok(()));
+        assert_eq!(game.try_shut(4), Err(GameError::AlreadyShut(4)));
+        assert_eq!(
+            game.try_play(TileSet::single(9)),
+            Err(GameError::AlreadyPlayed)
+        );
+        game.save_roll(12);
+        assert_eq!(
+            game.try_play(TileSet::from_tiles(&[5, 7])),
+            Err(GameError::AlreadyShut(5))
+        );
+        assert_eq!(game.get_shut(), vec![4, 5]);
+        assert_eq!(game.moves()[0].tiles, TileSet::from_tiles(&[4, 5]));
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        let mut game = ShutTheBox::init(9);
+        game.shut_tiles(TileSet::from_tiles(&[2, 7]));
+        let moves: Vec<Vec<usize>> = game.legal_moves(9).map(|x| x.to_vec()).collect();
+        assert_eq!(
+            moves,
+            vec![vec![9], vec![1, 8], vec![3, 6], vec![4, 5], vec![1, 3, 5]]
+        );
+        assert_eq!(game.legal_moves(9).at_most(1).count(), 1);
+        assert_eq!(game.legal_moves(2).count(), 0);
+        assert!(game.check_loss(2));
+    }
+
+    #[test]
+    fn test_try_save_game() {
+        let mut stats = Statistics::new(9);
+        let game = ShutTheBox::init(9);
+        assert_eq!(stats.try_save_game(&game), Err(GameError::NoRoll));
+        let mut game = ShutTheBox::init(12);
+        game.save_roll(12);
+        assert_eq!(
+            stats.try_save_game(&game),
+            Err(GameError::BoardSize {
+                expected: 9,
+                found: 12
+            })
+        );
+        let mut game = ShutTheBox::init(9);
+        game.save_roll(0);
+        assert_eq!(
+            stats.try_save_game(&game),
+            Err(GameError::RollOutOfRange(0))
+        );
+        assert_eq!(stats.num_total, 0);
+        // Rolls beyond two dice grow the roll counts
+        let mut game = ShutTheBox::init(9);
+        game.save_roll(13);
+        assert_eq!(stats.try_save_game(&game), Ok(()));
+        assert_eq!(stats.count_rawrolls.len(), 13);
+        assert_eq!(stats.count_lastroll[12], 1);
     }
 
     #[test]
@@ -433,7 +1055,7 @@ mod tests {
     fn test_shutthebox_init_9() {
         let max = 9;
         let game = ShutTheBox::init(max);
-        assert_eq!(game.status.capacity(), 9);
+        assert_eq!(game.total, 9);
         for ii in 1..max + 1 {
             assert_eq!(game.get_status(ii), Some(false));
         }
@@ -443,7 +1065,7 @@ mod tests {
     fn test_shutthebox_init_12() {
         let max = 12;
         let game = ShutTheBox::init(max);
-        assert_eq!(game.status.capacity(), 12);
+        assert_eq!(game.total, 12);
         for ii in 1..max + 1 {
             assert_eq!(game.get_status(ii), Some(false));
         }
@@ -453,7 +1075,7 @@ mod tests {
     fn test_shutthebox_outofbound() {
         let max = 9;
         let game = ShutTheBox::init(max);
-        assert_eq!(game.status.capacity(), 9);
+        assert_eq!(game.total, 9);
         assert_eq!(game.get_status(9), Some(false));
         assert_eq!(game.get_status(0), None);
         assert_eq!(game.get_status(10), None);
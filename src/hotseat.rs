@@ -0,0 +1,188 @@
+use crate::error::Error;
+use crate::game::ShutTheBox;
+use crate::score::ScoreRule;
+
+/// Smallest number of players in a hot-seat game
+pub const MIN_PLAYERS: usize = 2;
+
+/// Largest number of players in a hot-seat game
+pub const MAX_PLAYERS: usize = 8;
+
+/// Player is one seat at the table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    /// Running total of round scores
+    pub total: u64,
+    /// Score this round, once the player has taken their turn
+    pub round_score: Option<u64>,
+    /// Rounds won, including shared wins
+    pub wins: u64,
+}
+
+/// Outcome of a finished round
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundResult {
+    /// Players with the best score, or the player who shut the box
+    pub winners: Vec<usize>,
+    /// True if the round ended early because a player shut the box
+    pub shut: bool,
+}
+
+/// HotSeat tracks turns and round scores for players sharing one set of dice
+///
+/// Each player plays a whole board on their turn. A round ends once everyone has
+/// played, won by the lowest score, or immediately when a player shuts the box.
+/// The first player rotates every round.
+#[derive(Debug)]
+pub struct HotSeat {
+    pub players: Vec<Player>,
+    pub rule: ScoreRule,
+    /// Current round, starting from 1
+    pub round: u64,
+    /// Result of the current round, once it is over
+    pub result: Option<RoundResult>,
+    turn: usize, // Turns taken this round
+}
+
+impl HotSeat {
+    pub fn new(names: &[String], rule: ScoreRule) -> Result<HotSeat, Error> {
+        if names.len() < MIN_PLAYERS || names.len() > MAX_PLAYERS {
+            return Err(Error::Players(names.len()));
+        }
+        let players = names
+            .iter()
+            .map(|name| Player {
+                name: name.clone(),
+                total: 0,
+                round_score: None,
+                wins: 0,
+            })
+            .collect();
+        Ok(HotSeat {
+            players,
+            rule,
+            round: 1,
+            result: None,
+            turn: 0,
+        })
+    }
+
+    /// Index of the player whose turn it is
+    pub fn current(&self) -> usize {
+        let start = (self.round - 1) as usize;
+        (start + self.turn) % self.players.len()
+    }
+
+    /// True once the current round has a result
+    pub fn round_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// Score the finished board of the current player and pass the dice on
+    /// Returns the round result if this turn ended the round
+    pub fn end_turn(&mut self, game: &ShutTheBox) -> Option<&RoundResult> {
+        if self.round_over() {
+            return self.result.as_ref();
+        }
+        let current = self.current();
+        let score = game.score(self.rule);
+        let player = &mut self.players[current];
+        player.round_score = Some(score);
+        player.total += score;
+        self.turn += 1;
+
+        let result = if game.victory() {
+            RoundResult {
+                winners: vec![current],
+                shut: true,
+            }
+        } else if self.turn == self.players.len() {
+            let best = self.players.iter().filter_map(|x| x.round_score).min();
+            RoundResult {
+                winners: (0..self.players.len())
+                    .filter(|&ii| self.players[ii].round_score == best)
+                    .collect(),
+                shut: false,
+            }
+        } else {
+            return None;
+        };
+        for &ii in result.winners.iter() {
+            self.players[ii].wins += 1;
+        }
+        self.result = Some(result);
+        self.result.as_ref()
+    }
+
+    /// Start the next round
+    pub fn new_round(&mut self) {
+        self.round += 1;
+        self.turn = 0;
+        self.result = None;
+        for player in self.players.iter_mut() {
+            player.round_score = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::TileSet;
+
+    fn names(count: usize) -> Vec<String> {
+        (1..=count).map(|ii| format!("Player {}", ii)).collect()
+    }
+
+    /// A finished board with these tiles shut
+    fn board(shut: &[usize]) -> ShutTheBox {
+        let mut game = ShutTheBox::init(9);
+        game.shut_tiles(TileSet::from_tiles(shut));
+        game
+    }
+
+    #[test]
+    fn test_player_count() {
+        assert!(HotSeat::new(&names(1), ScoreRule::Sum).is_err());
+        assert!(HotSeat::new(&names(2), ScoreRule::Sum).is_ok());
+        assert!(HotSeat::new(&names(8), ScoreRule::Sum).is_ok());
+        assert!(HotSeat::new(&names(9), ScoreRule::Sum).is_err());
+    }
+
+    #[test]
+    fn test_round_lowest_score() {
+        let mut table = HotSeat::new(&names(3), ScoreRule::Sum).unwrap();
+        assert_eq!(table.current(), 0);
+        assert_eq!(table.end_turn(&board(&[9, 8])), None);
+        assert_eq!(table.current(), 1);
+        assert_eq!(table.end_turn(&board(&[9, 8, 7])), None);
+        let result = table.end_turn(&board(&[9, 8, 7])).unwrap().clone();
+        assert_eq!(result.winners, vec![1, 2]);
+        assert!(!result.shut);
+        assert_eq!(table.players[0].total, 28);
+        assert_eq!(table.players[1].wins, 1);
+        // The next round starts with the second player
+        table.new_round();
+        assert_eq!(table.current(), 1);
+        assert_eq!(table.players[1].round_score, None);
+    }
+
+    #[test]
+    fn test_round_shut_the_box() {
+        let mut table = HotSeat::new(&names(4), ScoreRule::Count).unwrap();
+        assert_eq!(table.end_turn(&board(&[1])), None);
+        let all: Vec<usize> = (1..=9).collect();
+        let result = table.end_turn(&board(&all)).unwrap().clone();
+        assert_eq!(
+            result,
+            RoundResult {
+                winners: vec![1],
+                shut: true
+            }
+        );
+        assert!(table.round_over());
+        assert_eq!(table.players[0].total, 8);
+        assert_eq!(table.players[2].round_score, None);
+    }
+}
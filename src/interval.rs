@@ -0,0 +1,304 @@
+use crate::error::Error;
+use std::str::FromStr;
+
+/// Confidence level of the intervals reported for simulations
+pub const CONFIDENCE: f64 = 0.95;
+
+/// Most continued fraction terms evaluated for the incomplete beta function
+const MAX_TERMS: usize = 100_000;
+
+/// Confidence interval for a probability
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    /// Half the width, i.e. the +/- precision of the estimate
+    pub fn half_width(&self) -> f64 {
+        self.width() / 2.0
+    }
+
+    pub fn contains(&self, prob: f64) -> bool {
+        self.lo <= prob && prob <= self.hi
+    }
+}
+
+/// Way of computing a binomial confidence interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalMethod {
+    /// Wilson score interval, cheap and close to nominal coverage
+    Wilson,
+    /// Clopper-Pearson interval from beta quantiles, never below nominal coverage
+    ClopperPearson,
+}
+
+impl IntervalMethod {
+    pub const ALL: [IntervalMethod; 2] = [IntervalMethod::Wilson, IntervalMethod::ClopperPearson];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntervalMethod::Wilson => "wilson",
+            IntervalMethod::ClopperPearson => "clopper-pearson",
+        }
+    }
+
+    /// Interval for the probability of success after `successes` out of `trials`
+    pub fn interval(&self, successes: u64, trials: u64, confidence: f64) -> Interval {
+        match self {
+            IntervalMethod::Wilson => wilson(successes, trials, confidence),
+            IntervalMethod::ClopperPearson => clopper_pearson(successes, trials, confidence),
+        }
+    }
+}
+
+impl FromStr for IntervalMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        IntervalMethod::ALL
+            .iter()
+            .find(|method| method.name() == s)
+            .copied()
+            .ok_or_else(|| Error::unknown("interval method", s))
+    }
+}
+
+/// Wilson score interval
+pub fn wilson(successes: u64, trials: u64, confidence: f64) -> Interval {
+    if trials == 0 {
+        return Interval { lo: 0.0, hi: 1.0 };
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z = normal_quantile(0.5 + confidence / 2.0);
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let spread = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    Interval {
+        lo: (center - spread).max(0.0),
+        hi: (center + spread).min(1.0),
+    }
+}
+
+/// Clopper-Pearson ("exact") interval
+pub fn clopper_pearson(successes: u64, trials: u64, confidence: f64) -> Interval {
+    if trials == 0 {
+        return Interval { lo: 0.0, hi: 1.0 };
+    }
+    let alpha = 1.0 - confidence;
+    let (k, n) = (successes as f64, trials as f64);
+    let lo = match successes {
+        0 => 0.0,
+        _ => beta_quantile(alpha / 2.0, k, n - k + 1.0),
+    };
+    let hi = match successes == trials {
+        true => 1.0,
+        false => beta_quantile(1.0 - alpha / 2.0, k + 1.0, n - k),
+    };
+    Interval { lo, hi }
+}
+
+/// Standard normal CDF (from a Chebyshev fit of erfc, relative error < 1.2e-7)
+pub fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, c| acc * t + c);
+    let erfc = t * (-z * z + poly).exp();
+    if x >= 0.0 {
+        1.0 - erfc / 2.0
+    } else {
+        erfc / 2.0
+    }
+}
+
+/// Inverse of the standard normal CDF (Acklam's approximation, relative error < 1.2e-9)
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Natural log of the gamma function (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const G: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = G
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |acc, (ii, g)| {
+            acc + g / (x + 1.0 + ii as f64)
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+fn regularized_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta function (modified Lentz's method)
+fn beta_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    d = 1.0 / if d.abs() < TINY { TINY } else { d };
+    let mut h = d;
+    for m in 1..MAX_TERMS {
+        let m = m as f64;
+        for &num in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ]
+        .iter()
+        {
+            d = 1.0 + num * d;
+            d = 1.0 / if d.abs() < TINY { TINY } else { d };
+            c = 1.0 + num / c;
+            c = if c.abs() < TINY { TINY } else { c };
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    h
+}
+
+/// Inverse of the regularized incomplete beta function, by bisection
+fn beta_quantile(p: f64, a: f64, b: f64) -> f64 {
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if regularized_beta(mid, a, b) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!((normal_quantile(0.975) - 1.959_964).abs() < 1e-6);
+        assert!((normal_quantile(0.005) + 2.575_829).abs() < 1e-6);
+        assert_eq!(normal_quantile(0.5), 0.0);
+        assert!((normal_cdf(1.959_964) - 0.975).abs() < 1e-6);
+        assert!((normal_cdf(-2.575_829) - 0.005).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_wilson() {
+        let half = wilson(5, 10, 0.95);
+        assert!(close(half.lo, 0.2366) && close(half.hi, 0.7634));
+        let none = wilson(0, 10, 0.95);
+        assert!(close(none.lo, 0.0) && close(none.hi, 0.2775));
+        assert_eq!(wilson(0, 0, 0.95), Interval { lo: 0.0, hi: 1.0 });
+    }
+
+    #[test]
+    fn test_clopper_pearson() {
+        let half = clopper_pearson(5, 10, 0.95);
+        assert!(close(half.lo, 0.1871) && close(half.hi, 0.8129));
+        let none = clopper_pearson(0, 10, 0.95);
+        assert_eq!(none.lo, 0.0);
+        assert!(close(none.hi, 0.3085));
+        // Never narrower than Wilson, even for large samples
+        let (k, n) = (70_000, 1_000_000);
+        let exact = clopper_pearson(k, n, 0.95);
+        assert!(exact.contains(0.07));
+        assert!(exact.width() >= wilson(k, n, 0.95).width());
+        assert!(exact.width() < 1.1 * wilson(k, n, 0.95).width());
+        let method: IntervalMethod = "clopper-pearson".parse().unwrap();
+        assert_eq!(method, IntervalMethod::ClopperPearson);
+    }
+}
@@ -0,0 +1,31 @@
+//! Shut the Box game engine
+//!
+//! The board and move history live in [`game`], with simulation and
+//! [`Statistics`] for many games at once, and the dice in [`dice`].
+//! Strategies that pick the tiles to shut are in [`strategy`] and [`solver`],
+//! and the house rules in [`rules`]. The terminal UI in the `shutthebox`
+//! binary is built on this library.
+
+pub mod dice;
+pub mod error;
+pub mod exact;
+pub mod export;
+pub mod game;
+pub mod hotseat;
+pub mod interval;
+pub mod replay;
+pub mod review;
+pub mod rules;
+pub mod score;
+pub mod solver;
+pub mod strategy;
+pub mod tiles;
+pub mod tournament;
+
+pub use dice::{Dice, DiceConfig};
+pub use error::{Error, GameError, Result};
+pub use game::{simulate_batch, simulate_game, simulate_seeded, Move, ShutTheBox, Statistics};
+pub use rules::{OneDie, Rules};
+pub use score::ScoreRule;
+pub use strategy::{Strategy, StrategyKind};
+pub use tiles::TileSet;
@@ -1,16 +1,20 @@
 #[allow(dead_code)]
 mod app;
-pub mod game;
+mod cli;
 mod ui;
 
 use crate::app::App;
+use crate::cli::Options;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use shutthebox::replay::Replay;
 use std::{
+    env,
     error::Error,
+    fs,
     io::{stdout, Write},
     sync::mpsc,
     thread,
@@ -24,6 +28,43 @@ enum Event<I> {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, flags) = match args.first().map(|x| x.as_str()) {
+        Some("play") | Some("simulate") | Some("merge") | Some("bench") | Some("compare")
+        | Some("replay") => (args[0].as_str(), &args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        // Only flags may follow the program name when the play command is left out
+        Some(command) if !command.starts_with('-') => {
+            eprintln!("Unknown command: {}\n\n{}", command, cli::USAGE);
+            std::process::exit(2);
+        }
+        _ => ("play", &args[..]),
+    };
+    let options = match Options::parse(flags) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    match command {
+        "simulate" => cli::simulate(&options),
+        "merge" => cli::merge(&options),
+        "bench" => cli::bench(&options),
+        "compare" => cli::compare(&options),
+        "replay" => {
+            let path = options.files.first().ok_or("replay needs a replay file")?;
+            let replay = Replay::from_json(&fs::read_to_string(path)?)?;
+            run_tui(&options, Some(replay))
+        }
+        _ => run_tui(&options, None),
+    }
+}
+
+fn run_tui(options: &Options, replay: Option<Replay>) -> Result<(), Box<dyn Error>> {
     enable_raw_mode()?;
 
     let mut stdout = stdout();
@@ -54,7 +95,16 @@ fn main() -> Result<(), Box<dyn Error>> {
     });
 
     let mut app = App::new("Shut the Box!");
+    app.strategy = options.strategy;
+    app.seed = options.seed;
+    app.set_rules(options.rules.clone());
+    if !options.players.is_empty() {
+        app.players = options.players.clone();
+    }
     app.tasks.state.select(Some(0));
+    if let Some(replay) = replay {
+        app.load_replay(replay);
+    }
 
     terminal.clear()?;
 
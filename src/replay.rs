@@ -0,0 +1,129 @@
+use crate::error::Error;
+use crate::game::{Move, ShutTheBox};
+use crate::rules::Rules;
+use crate::tiles::TileSet;
+use serde::{Deserialize, Serialize};
+
+/// Replay holds everything needed to step through a game again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub rules: Rules,
+    /// Seed the dice of this game were created with, if any
+    pub seed: Option<u64>,
+    pub moves: Vec<Move>,
+}
+
+impl Replay {
+    pub fn new(game: &ShutTheBox, rules: Rules, seed: Option<u64>) -> Replay {
+        Replay {
+            rules,
+            seed,
+            moves: game.moves().to_vec(),
+        }
+    }
+
+    /// Compact single-line JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Load a replay written by `to_json`, checking that every move is legal
+    pub fn from_json(text: &str) -> Result<Replay, Error> {
+        let mut replay: Replay = serde_json::from_str(text)?;
+        replay.validate()?;
+        Ok(replay)
+    }
+
+    /// Check every move against the board and fill in the tiles open before it
+    fn validate(&mut self) -> Result<(), Error> {
+        self.rules.validate()?;
+        let mut open = TileSet::full(self.rules.tiles);
+        let last = self.moves.len().saturating_sub(1);
+        for (ii, played) in self.moves.iter_mut().enumerate() {
+            let error = |reason: &str| Err(Error::Replay(format!("move {} {}", ii + 1, reason)));
+            if played.dice.len() > self.rules.dice.count {
+                return error("throws more dice than the rules allow");
+            }
+            let sides = self.rules.dice.sides;
+            if played.dice.iter().any(|x| x == 0 || x > sides) {
+                return error("rolls a face the dice do not have");
+            }
+            if !played.dice.is_empty() && played.dice.iter().sum::<usize>() != played.sum {
+                return error("does not match its dice");
+            }
+            let shut = played.tiles;
+            if !open.is_superset(shut) {
+                return error("shuts a tile that is not open");
+            }
+            if shut.len() > self.rules.shut_limit() {
+                return error("shuts more tiles than the rules allow");
+            }
+            if shut.is_empty() && ii != last {
+                return error("is not played but the game goes on");
+            }
+            if !shut.is_empty() && shut.sum() != played.sum {
+                return error("does not add up to its roll");
+            }
+            played.board_before = open;
+            open = open.difference(shut);
+        }
+        Ok(())
+    }
+
+    /// Board after the first `step` moves
+    pub fn game_at(&self, step: usize) -> ShutTheBox {
+        let mut game = ShutTheBox::with_rules(&self.rules);
+        for played in self.moves.iter().take(step) {
+            game.save_move(&played.dice.to_vec(), played.sum);
+            game.shut_tiles(played.tiles);
+        }
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::DiceConfig;
+    use crate::game::simulate_seeded;
+    use crate::rules::OneDie;
+    use crate::strategy::Greedy;
+
+    #[test]
+    fn test_replay_roundtrip() {
+        let rules = Rules {
+            tiles: 9,
+            one_die: OneDie::May,
+            max_shut: Some(2),
+            dice: DiceConfig::fair(2, 8),
+        };
+        let game = simulate_seeded(&mut Greedy, 7, &rules);
+        let replay = Replay::new(&game, rules, Some(7));
+        let text = replay.to_json().unwrap();
+        assert!(!text.contains('\n'));
+        let loaded = Replay::from_json(&text).unwrap();
+        assert_eq!(loaded, replay);
+        let end = loaded.game_at(loaded.moves.len());
+        assert_eq!(end.open_tiles(), game.open_tiles());
+        assert_eq!(end.get_rolls(), game.get_rolls());
+        assert_eq!(loaded.game_at(1).moves(), &game.moves()[..1]);
+    }
+
+    #[test]
+    fn test_replay_invalid() {
+        let rules = serde_json::to_string(&Rules::new(9)).unwrap();
+        let text =
+            |moves: &str| format!(r#"{{"rules":{},"seed":null,"moves":[{}]}}"#, rules, moves);
+        assert!(Replay::from_json(&text(r#"{"dice":[6,3],"sum":9,"tiles":[9]}"#)).is_ok());
+        assert!(Replay::from_json(&text(r#"{"dice":[6,2],"sum":9,"tiles":[9]}"#)).is_err());
+        assert!(Replay::from_json(&text(r#"{"dice":[6,3],"sum":9,"tiles":[4,4,1]}"#)).is_err());
+        assert!(Replay::from_json(&text(r#"{"dice":[5,3],"sum":8,"tiles":[9]}"#)).is_err());
+        assert!(Replay::from_json(&text(r#"{"dice":[7,2],"sum":9,"tiles":[9]}"#)).is_err());
+        assert!(Replay::from_json(&text(r#"{"dice":[3,3,3],"sum":9,"tiles":[9]}"#)).is_err());
+        assert!(Replay::from_json(&text(r#"{"dice":[0,9],"sum":9,"tiles":[9]}"#)).is_err());
+        assert!(Replay::from_json(&text(r#"{"dice":[],"sum":9,"tiles":[9]}"#)).is_ok());
+        let shut_twice = r#"{"dice":[6,3],"sum":9,"tiles":[9]},{"dice":[6,3],"sum":9,"tiles":[9]}"#;
+        assert!(Replay::from_json(&text(shut_twice)).is_err());
+        assert!(matches!(Replay::from_json("{}"), Err(Error::Json(_))));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::game::ShutTheBox;
+use crate::solver::Solver;
+use crate::tiles::TileSet;
+
+/// Fraction of the best move's value a move may give up before it is a blunder
+pub const BLUNDER_THRESHOLD: f64 = 0.1;
+
+/// MoveReview compares one roll of a finished game with the solver's choice
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveReview {
+    pub roll: usize,
+    /// Tiles open before the move
+    pub before: TileSet,
+    /// Tiles shut, or None if the roll could not be played
+    pub chosen: Option<TileSet>,
+    /// The solver's move for this roll
+    pub best: Option<TileSet>,
+    /// Objective value of the board left by the chosen move
+    pub chosen_value: f64,
+    /// Objective value of the board left by the best move
+    pub best_value: f64,
+}
+
+impl MoveReview {
+    /// Share of the best move's value lost by the chosen move (0 for the best move)
+    pub fn relative_loss(&self, solver: &Solver) -> f64 {
+        let (best, chosen) = (self.best_value, self.chosen_value);
+        let loss = if solver.objective().maximize() {
+            best - chosen
+        } else {
+            chosen - best
+        };
+        if loss <= 0.0 {
+            0.0
+        } else if best == 0.0 {
+            // Only reachable when minimizing from a perfect expected score
+            1.0
+        } else {
+            loss / best.abs()
+        }
+    }
+
+    /// True if the chosen move gave up more than `BLUNDER_THRESHOLD` of the best value
+    pub fn is_blunder(&self, solver: &Solver) -> bool {
+        self.relative_loss(solver) > BLUNDER_THRESHOLD
+    }
+}
+
+/// Rate every roll of a game against the solver's best move
+pub fn review(game: &ShutTheBox, solver: &Solver) -> Vec<MoveReview> {
+    let mut out = Vec::with_capacity(game.moves().len());
+    for played in game.moves() {
+        let (open, roll, tiles) = (played.board_before, played.sum, played.tiles);
+        let best = solver.best_move(open, roll);
+        // Every legal move shuts at least one tile
+        let chosen = if tiles.is_empty() { None } else { Some(tiles) };
+        let value = |x: Option<TileSet>| match x {
+            Some(x) => solver.move_value(open, x),
+            None => solver.objective().terminal(open),
+        };
+        out.push(MoveReview {
+            roll,
+            before: open,
+            chosen,
+            best,
+            chosen_value: value(chosen),
+            best_value: value(best),
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+    use crate::solver::Objective;
+
+    #[test]
+    fn test_review() {
+        let solver = Solver::new(&Rules::new(9), Objective::WinProbability);
+        let mut game = ShutTheBox::init(9);
+        game.save_roll(9);
+        game.shut_tiles(TileSet::single(9));
+        game.save_roll(8);
+        game.shut_tiles(TileSet::from_tiles(&[1, 2, 5]));
+        game.save_roll(12);
+        let moves = review(&game, &solver);
+        assert_eq!(moves.len(), 3);
+        assert_eq!(moves[0].chosen, moves[0].best);
+        assert!(!moves[0].is_blunder(&solver));
+        // Splitting 8 into three small tiles is far worse than shutting the 8
+        assert_eq!(moves[1].best, Some(TileSet::single(8)));
+        assert_eq!(moves[1].before, TileSet::full(8));
+        assert!(moves[1].chosen_value < moves[1].best_value);
+        assert!(moves[1].is_blunder(&solver));
+        assert_eq!(moves[2].chosen, None);
+        assert!(moves[2].best.is_some());
+        assert_eq!(moves[2].chosen_value, 0.0);
+    }
+}
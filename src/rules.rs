@@ -0,0 +1,194 @@
+use crate::dice::{Dice, DiceConfig};
+use crate::error::Error;
+use crate::game::DEFAULT_TILES;
+use crate::strategy::Strategy;
+use crate::tiles::{TileSet, MAX_TILES};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// When a player may roll one die instead of two
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OneDie {
+    /// Always roll both dice
+    Never,
+    /// The player chooses once every tile above the sides of a die is shut
+    May,
+    /// Roll one die once every tile above the sides of a die is shut
+    Must,
+}
+
+impl OneDie {
+    pub const ALL: [OneDie; 3] = [OneDie::Never, OneDie::May, OneDie::Must];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OneDie::Never => "never",
+            OneDie::May => "may",
+            OneDie::Must => "must",
+        }
+    }
+
+    /// Cycle to the next variant in the list
+    pub fn next(&self) -> OneDie {
+        let idx = OneDie::ALL.iter().position(|x| x == self).unwrap();
+        OneDie::ALL[(idx + 1) % OneDie::ALL.len()]
+    }
+}
+
+impl FromStr for OneDie {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OneDie::ALL
+            .iter()
+            .find(|rule| rule.name() == s)
+            .copied()
+            .ok_or_else(|| Error::unknown("one-die rule", s))
+    }
+}
+
+/// Rules is the configuration of a game variant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rules {
+    /// Number of tiles on the board
+    pub tiles: usize,
+    /// When a single die may be rolled
+    pub one_die: OneDie,
+    /// Most tiles one roll may shut, or None for any number
+    pub max_shut: Option<usize>,
+    /// Dice thrown for every roll
+    pub dice: DiceConfig,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::new(DEFAULT_TILES)
+    }
+}
+
+impl Rules {
+    /// Standard rules on a board of `tiles` tiles
+    pub fn new(tiles: usize) -> Rules {
+        Rules {
+            tiles,
+            one_die: OneDie::Never,
+            max_shut: None,
+            dice: DiceConfig::default(),
+        }
+    }
+
+    /// Check that the rules describe a playable board
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.tiles == 0 || self.tiles > MAX_TILES {
+            return Err(Error::Tiles(self.tiles));
+        }
+        if self.max_shut == Some(0) {
+            return Err(Error::MaxShut);
+        }
+        self.dice.validate()
+    }
+
+    /// Most tiles one roll may shut, counting no limit as every tile
+    pub fn shut_limit(&self) -> usize {
+        self.max_shut.unwrap_or(MAX_TILES)
+    }
+
+    /// True if a single die may be rolled with these tiles open
+    pub fn one_die_allowed(&self, open: TileSet) -> bool {
+        self.one_die != OneDie::Never && open.max().unwrap_or(0) <= self.dice.sides
+    }
+
+    /// True if the next roll uses one die, asking the strategy when it is optional
+    pub fn roll_one_die(&self, open: TileSet, strategy: &mut dyn Strategy) -> bool {
+        match self.one_die {
+            OneDie::Never => false,
+            OneDie::May => self.one_die_allowed(open) && strategy.one_die(open, self),
+            OneDie::Must => self.one_die_allowed(open),
+        }
+    }
+
+    /// Roll one or all of the dice for the next turn
+    pub fn roll(&self, dice: &mut Dice, open: TileSet, strategy: &mut dyn Strategy) {
+        if self.roll_one_die(open, strategy) {
+            dice.roll_one();
+        } else {
+            dice.roll();
+        }
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} tiles, one die {}", self.tiles, self.one_die.name())?;
+        if let Some(max) = self.max_shut {
+            write!(f, ", at most {} shut per roll", max)?;
+        }
+        if self.dice != DiceConfig::default() {
+            write!(f, ", {} dice", self.dice)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::Greedy;
+
+    #[test]
+    fn test_one_die_allowed() {
+        let mut rules = Rules::new(9);
+        let low = TileSet::from_tiles(&[1, 4, 6]);
+        let high = TileSet::from_tiles(&[1, 7]);
+        assert!(!rules.one_die_allowed(low));
+        assert!(!rules.roll_one_die(low, &mut Greedy));
+        rules.one_die = OneDie::Must;
+        assert!(rules.one_die_allowed(low));
+        assert!(!rules.one_die_allowed(high));
+        assert!(rules.roll_one_die(low, &mut Greedy));
+        rules.one_die = OneDie::May;
+        // Only tile 1 is open, which two dice can never roll
+        assert!(rules.roll_one_die(TileSet::single(1), &mut Greedy));
+        assert!(!rules.roll_one_die(high, &mut Greedy));
+    }
+
+    #[test]
+    fn test_one_die_parse() {
+        for rule in OneDie::ALL.iter() {
+            assert_eq!(rule.name().parse::<OneDie>().unwrap(), *rule);
+            assert_eq!(rule.next().next().next(), *rule);
+        }
+        assert!("sometimes".parse::<OneDie>().is_err());
+    }
+
+    #[test]
+    fn test_max_shut() {
+        let mut rules = Rules::new(9);
+        assert_eq!(rules.shut_limit(), MAX_TILES);
+        assert_eq!(rules.to_string(), "9 tiles, one die never");
+        rules.max_shut = Some(2);
+        assert_eq!(rules.shut_limit(), 2);
+        assert_eq!(
+            rules.to_string(),
+            "9 tiles, one die never, at most 2 shut per roll"
+        );
+        rules.max_shut = Some(0);
+        assert!(rules.validate().is_err());
+    }
+
+    #[test]
+    fn test_rules_dice() {
+        let mut rules = Rules::new(18);
+        rules.dice = DiceConfig::fair(3, 6);
+        assert_eq!(rules.to_string(), "18 tiles, one die never, 3d6 dice");
+        rules.dice = DiceConfig::fair(2, 8);
+        rules.one_die = OneDie::Must;
+        // Eight-sided dice can roll one die with tiles 7 and 8 still open
+        assert!(rules.one_die_allowed(TileSet::from_tiles(&[1, 8])));
+        assert!(!rules.one_die_allowed(TileSet::from_tiles(&[1, 9])));
+        rules.dice = DiceConfig::fair(2, 1);
+        assert!(rules.validate().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::Error;
+use crate::tiles::TileSet;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Number of games added up into one round of golf
+pub const GOLF_GAMES: u64 = 9;
+
+/// Concatenate the digits of the open tiles, i.e. open 1,4 scores 14
+/// Saturates at `u64::MAX`, which large boards can reach with most tiles open
+pub fn digit_score(open: TileSet) -> u64 {
+    open.iter().fold(0, |acc: u64, x| {
+        let mut shift = 10;
+        while shift <= x as u64 {
+            shift *= 10;
+        }
+        acc.saturating_mul(shift).saturating_add(x as u64)
+    })
+}
+
+/// House rule used to score the tiles left open at the end of a game (lower is better)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreRule {
+    /// Sum of the open tiles
+    Sum,
+    /// Digits of the open tiles concatenated
+    Digits,
+    /// Number of open tiles
+    Count,
+    /// Sum of the open tiles, added up over a round of `GOLF_GAMES` games
+    Golf,
+}
+
+impl ScoreRule {
+    pub const ALL: [ScoreRule; 4] = [
+        ScoreRule::Sum,
+        ScoreRule::Digits,
+        ScoreRule::Count,
+        ScoreRule::Golf,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreRule::Sum => "sum",
+            ScoreRule::Digits => "digits",
+            ScoreRule::Count => "count",
+            ScoreRule::Golf => "golf",
+        }
+    }
+
+    /// Position of this rule in `ALL`
+    pub fn index(&self) -> usize {
+        ScoreRule::ALL.iter().position(|x| x == self).unwrap()
+    }
+
+    /// Cycle to the next rule in the list
+    pub fn next(&self) -> ScoreRule {
+        ScoreRule::ALL[(self.index() + 1) % ScoreRule::ALL.len()]
+    }
+
+    /// Score of a single game that ended with these tiles open
+    pub fn score(&self, open: TileSet) -> u64 {
+        match self {
+            ScoreRule::Sum | ScoreRule::Golf => open.sum() as u64,
+            ScoreRule::Digits => digit_score(open),
+            ScoreRule::Count => open.len() as u64,
+        }
+    }
+}
+
+impl FromStr for ScoreRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ScoreRule::ALL
+            .iter()
+            .find(|rule| rule.name() == s)
+            .copied()
+            .ok_or_else(|| Error::unknown("score rule", s))
+    }
+}
+
+/// Running golf score of a round that is still being played
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GolfCard {
+    /// Score so far this round
+    pub total: u64,
+    /// Games played so far this round
+    pub games: u64,
+}
+
+impl GolfCard {
+    /// Add the score of one game, returning the round total once the round is complete
+    pub fn add(&mut self, score: u64) -> Option<u64> {
+        self.total += score;
+        self.games += 1;
+        if self.games < GOLF_GAMES {
+            return None;
+        }
+        let total = self.total;
+        *self = GolfCard::default();
+        Some(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(tiles: &[usize]) -> TileSet {
+        TileSet::from_tiles(tiles)
+    }
+
+    #[test]
+    fn test_digit_score() {
+        assert_eq!(digit_score(TileSet::EMPTY), 0);
+        assert_eq!(digit_score(set(&[1, 4])), 14);
+        assert_eq!(digit_score(set(&[1, 10, 12])), 11012);
+        assert_eq!(digit_score(TileSet::full(18)), u64::MAX);
+    }
+
+    #[test]
+    fn test_score_rules() {
+        let open = set(&[1, 4, 9]);
+        assert_eq!(ScoreRule::Sum.score(open), 14);
+        assert_eq!(ScoreRule::Digits.score(open), 149);
+        assert_eq!(ScoreRule::Count.score(open), 3);
+        assert_eq!(ScoreRule::Golf.score(open), 14);
+        for rule in ScoreRule::ALL.iter() {
+            assert_eq!(rule.score(TileSet::EMPTY), 0);
+            assert_eq!(rule.name().parse::<ScoreRule>().unwrap(), *rule);
+        }
+    }
+
+    #[test]
+    fn test_golf_card() {
+        let mut card = GolfCard::default();
+        for ii in 1..GOLF_GAMES {
+            assert_eq!(card.add(ii), None);
+        }
+        assert_eq!(card.add(10), Some(46));
+        assert_eq!(card, GolfCard::default());
+    }
+}
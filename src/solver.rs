@@ -0,0 +1,292 @@
+use crate::game::ShutTheBox;
+use crate::rules::{OneDie, Rules};
+use crate::score::digit_score;
+use crate::strategy::Strategy;
+use crate::tiles::{Subsets, TileSet};
+
+/// Largest board the solver handles (solving visits 3^N state/move pairs)
+pub const MAX_SOLVER_TILES: usize = 16;
+
+/// Quantity the solver optimizes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    /// Maximize the probability of shutting every tile
+    WinProbability,
+    /// Minimize the expected sum of the open tiles at the end of the game
+    ExpectedSum,
+    /// Minimize the expected digit-concatenated score of the open tiles
+    ExpectedDigits,
+}
+
+impl Objective {
+    pub const ALL: [Objective; 3] = [
+        Objective::WinProbability,
+        Objective::ExpectedSum,
+        Objective::ExpectedDigits,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::WinProbability => "P(win)",
+            Objective::ExpectedSum => "E[sum]",
+            Objective::ExpectedDigits => "E[digits]",
+        }
+    }
+
+    /// True if larger values are better
+    pub fn maximize(&self) -> bool {
+        *self == Objective::WinProbability
+    }
+
+    /// Final value of a game that ends with these tiles open
+    pub fn terminal(&self, open: TileSet) -> f64 {
+        match self {
+            Objective::WinProbability => open.is_empty() as u8 as f64,
+            Objective::ExpectedSum => open.sum() as f64,
+            Objective::ExpectedDigits => digit_score(open) as f64,
+        }
+    }
+
+    /// True if value `a` is strictly better than value `b`
+    pub fn better(&self, a: f64, b: f64) -> bool {
+        if self.maximize() {
+            a > b
+        } else {
+            a < b
+        }
+    }
+}
+
+/// Solver computes optimal play for every board state by dynamic programming
+///
+/// Board states are indexed by the bitmask of OPEN tiles. The value of a state is
+/// the objective (win probability or expected score) from it before the dice are
+/// rolled, assuming optimal play from then on. The best move for a roll does not
+/// depend on how many dice produced it, so only the one-die choice is stored apart.
+pub struct Solver {
+    total: usize,
+    objective: Objective,
+    rolls: Vec<f64>,    // Probability of each roll, indexed by roll
+    value: Vec<f64>,    // Objective value, indexed by open mask
+    policy: Vec<u32>,   // Best move mask, indexed by open mask * rolls + roll (0 = no move)
+    one_die: Vec<bool>, // Whether to roll one die, indexed by open mask
+}
+
+impl Solver {
+    /// Solve every state of a game played with `rules`
+    pub fn new(rules: &Rules, objective: Objective) -> Solver {
+        let total = rules.tiles;
+        assert!(
+            total <= MAX_SOLVER_TILES,
+            "The solver handles at most {} tiles",
+            MAX_SOLVER_TILES
+        );
+        let rolls = rules.dice.distribution();
+        let single = rules.dice.one_die_distribution();
+        let nrolls = rolls.len();
+        let nstates = 1 << total;
+        let limit = rules.shut_limit();
+
+        // Precompute the tile sum of every mask
+        let mut sums = vec![0; nstates];
+        for mask in 1..nstates {
+            let low = mask.trailing_zeros() as usize;
+            sums[mask] = sums[mask & (mask - 1)] + low + 1;
+        }
+
+        let mut value = vec![0.0; nstates];
+        let mut policy = vec![0; nstates * nrolls];
+        let mut one_die = vec![false; nstates];
+        value[0] = objective.terminal(TileSet::EMPTY);
+        let mut best: Vec<Option<f64>> = vec![None; nrolls];
+        // Shutting tiles always moves to a smaller mask, so solve in ascending order
+        for mask in 1..nstates {
+            for val in best.iter_mut() {
+                *val = None;
+            }
+            let row = &mut policy[mask * nrolls..(mask + 1) * nrolls];
+            let mut sub = mask;
+            while sub > 0 {
+                let roll = sums[sub];
+                let allowed = sub.count_ones() as usize <= limit;
+                if allowed && roll < nrolls && (rolls[roll] > 0.0 || single[roll] > 0.0) {
+                    let next = value[mask ^ sub];
+                    let improves = match best[roll] {
+                        Some(val) => objective.better(next, val),
+                        None => true,
+                    };
+                    if improves {
+                        best[roll] = Some(next);
+                        row[roll] = sub as u32;
+                    }
+                }
+                sub = (sub - 1) & mask;
+            }
+            // Rolls without a legal move end the game with these tiles open
+            let lost = objective.terminal(TileSet(mask as u32));
+            let expected = |probs: &[f64]| -> f64 {
+                probs
+                    .iter()
+                    .zip(best.iter())
+                    .map(|(prob, val)| prob * val.unwrap_or(lost))
+                    .sum()
+            };
+            let two = expected(&rolls);
+            value[mask] = two;
+            if rules.one_die_allowed(TileSet(mask as u32)) {
+                let one = expected(&single);
+                if rules.one_die == OneDie::Must || objective.better(one, two) {
+                    value[mask] = one;
+                    one_die[mask] = true;
+                }
+            }
+        }
+
+        Solver {
+            total,
+            objective,
+            rolls,
+            value,
+            policy,
+            one_die,
+        }
+    }
+
+    /// Number of tiles on the solved board
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Objective this solver optimized
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Objective value from a set of open tiles, before rolling
+    pub fn value(&self, open: TileSet) -> f64 {
+        self.value[open.0 as usize]
+    }
+
+    /// Objective value of the current game, before rolling
+    pub fn game_value(&self, game: &ShutTheBox) -> f64 {
+        self.value(game.open_tiles())
+    }
+
+    /// Best tiles to shut from a set of open tiles for a given roll
+    pub fn best_move(&self, open: TileSet, roll: usize) -> Option<TileSet> {
+        if roll >= self.rolls.len() {
+            return None;
+        }
+        match self.policy[open.0 as usize * self.rolls.len() + roll] {
+            0 => None,
+            sub => Some(TileSet(sub)),
+        }
+    }
+
+    /// True if one die should be rolled from a set of open tiles, when allowed
+    pub fn roll_one_die(&self, open: TileSet) -> bool {
+        self.one_die[open.0 as usize]
+    }
+
+    /// Objective value after shutting `tiles` from a set of open tiles
+    pub fn move_value(&self, open: TileSet, tiles: TileSet) -> f64 {
+        self.value(open.difference(tiles))
+    }
+}
+
+impl Strategy for Solver {
+    /// The policy already only holds moves the solved rules allow
+    fn choose_from(&mut self, open: TileSet, roll: usize, _moves: Subsets) -> Option<TileSet> {
+        self.best_move(open, roll)
+    }
+
+    /// The solved rules already fix the dice
+    fn one_die(&mut self, open: TileSet, _rules: &Rules) -> bool {
+        self.roll_one_die(open)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::subsets;
+
+    fn set(tiles: &[usize]) -> TileSet {
+        TileSet::from_tiles(tiles)
+    }
+
+    #[test]
+    fn test_solver_small_board() {
+        let solver = Solver::new(&Rules::new(3), Objective::WinProbability);
+        assert!((solver.value(set(&[2])) - 1.0 / 36.0).abs() < 1e-12);
+        assert_eq!(solver.value(set(&[1])), 0.0);
+        assert!((solver.value(set(&[1, 3])) - 3.0 / 36.0).abs() < 1e-12);
+        let game = ShutTheBox::init(3);
+        assert!((solver.game_value(&game) - 190.0 / 1296.0).abs() < 1e-12);
+        assert_eq!(solver.best_move(set(&[1, 2, 3]), 6), Some(set(&[1, 2, 3])));
+        assert_eq!(solver.best_move(set(&[1, 2, 3]), 7), None);
+    }
+
+    #[test]
+    fn test_solver_expected_score() {
+        let game = ShutTheBox::init(2);
+        let solver = Solver::new(&Rules::new(2), Objective::ExpectedSum);
+        assert!((solver.game_value(&game) - 100.0 / 36.0).abs() < 1e-12);
+        assert!((solver.value(set(&[1])) - 1.0).abs() < 1e-12);
+        let solver = Solver::new(&Rules::new(2), Objective::ExpectedDigits);
+        assert!((solver.game_value(&game) - 397.0 / 36.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_solver_policy_is_optimal() {
+        let open = TileSet::full(12);
+        for &objective in Objective::ALL.iter() {
+            let solver = Solver::new(&Rules::new(12), objective);
+            for roll in 2..13 {
+                let best = solver.best_move(open, roll).unwrap();
+                assert_eq!(best.sum(), roll);
+                let best_value = solver.move_value(open, best);
+                for tiles in subsets(open, roll) {
+                    let value = solver.move_value(open, tiles);
+                    assert!(!objective.better(value, best_value));
+                }
+            }
+            assert_eq!(
+                solver.value(TileSet::EMPTY),
+                objective.terminal(TileSet::EMPTY)
+            );
+        }
+    }
+
+    #[test]
+    fn test_solver_max_shut() {
+        let mut rules = Rules::new(9);
+        let any = Solver::new(&rules, Objective::WinProbability);
+        rules.max_shut = Some(2);
+        let capped = Solver::new(&rules, Objective::WinProbability);
+        let open = TileSet::full(9);
+        assert!(capped.value(open) < any.value(open));
+        // A roll of 6 on {1, 2, 3} would have to shut all three tiles
+        assert_eq!(any.best_move(set(&[1, 2, 3]), 6), Some(set(&[1, 2, 3])));
+        assert_eq!(capped.best_move(set(&[1, 2, 3]), 6), None);
+        assert!(capped.value(set(&[1, 2, 3])) < any.value(set(&[1, 2, 3])));
+    }
+
+    #[test]
+    fn test_solver_one_die() {
+        let mut rules = Rules::new(2);
+        rules.one_die = OneDie::May;
+        let solver = Solver::new(&rules, Objective::WinProbability);
+        // Tile 1 alone can only be shut with one die
+        assert!(solver.roll_one_die(set(&[1])));
+        assert!((solver.value(set(&[1])) - 1.0 / 6.0).abs() < 1e-12);
+        // {1, 2}: one die rolls 3 (1/6) or 1 or 2 then the other (2/6 * 1/6), while two dice
+        // only win by rolling 3 (2/36) or 2 then 1 (1/36 * 1/6)
+        assert!(solver.roll_one_die(set(&[1, 2])));
+        assert!((solver.value(set(&[1, 2])) - 8.0 / 36.0).abs() < 1e-12);
+        rules.one_die = OneDie::Never;
+        let never = Solver::new(&rules, Objective::WinProbability);
+        assert!(!never.roll_one_die(set(&[1, 2])));
+        assert!(never.value(set(&[1, 2])) < solver.value(set(&[1, 2])));
+    }
+}
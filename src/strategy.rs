@@ -0,0 +1,316 @@
+use crate::error::Error;
+use crate::rules::Rules;
+use crate::solver::{Objective, Solver, MAX_SOLVER_TILES};
+use crate::tiles::{subsets, Subsets, TileSet};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::str::FromStr;
+
+/// Strategy decides which open tiles to shut for a given roll
+pub trait Strategy {
+    /// Return one of the legal `moves` for the roll, or None if there are none
+    fn choose_from(&mut self, open: TileSet, roll: usize, moves: Subsets) -> Option<TileSet>;
+
+    /// Return the tiles to shut when any number of tiles may be shut per roll
+    fn choose(&mut self, open: TileSet, roll: usize) -> Option<TileSet> {
+        self.choose_from(open, roll, subsets(open, roll))
+    }
+
+    /// Reset any internal randomness so a game can be reproduced
+    fn reseed(&mut self, _seed: u64) {}
+
+    /// Return true to roll one die instead of all of them, when the rules allow either
+    ///
+    /// By default, roll whichever is more likely to leave a legal move.
+    fn one_die(&mut self, open: TileSet, rules: &Rules) -> bool {
+        let limit = rules.shut_limit();
+        let playable = |probs: Vec<f64>| -> f64 {
+            probs
+                .iter()
+                .enumerate()
+                .filter(|&(roll, _)| subsets(open, roll).at_most(limit).next().is_some())
+                .map(|(_, prob)| prob)
+                .sum()
+        };
+        // Equal chances keep all the dice, despite rounding
+        playable(rules.dice.one_die_distribution()) > playable(rules.dice.distribution()) + 1e-12
+    }
+}
+
+/// Selectable list of the built-in strategies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrategyKind {
+    Greedy,
+    HighestFirst,
+    FewestTiles,
+    MostTiles,
+    Random,
+    Optimal,
+    OptimalSum,
+    OptimalDigits,
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 8] = [
+        StrategyKind::Greedy,
+        StrategyKind::HighestFirst,
+        StrategyKind::FewestTiles,
+        StrategyKind::MostTiles,
+        StrategyKind::Random,
+        StrategyKind::Optimal,
+        StrategyKind::OptimalSum,
+        StrategyKind::OptimalDigits,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrategyKind::Greedy => "greedy",
+            StrategyKind::HighestFirst => "highest",
+            StrategyKind::FewestTiles => "fewest",
+            StrategyKind::MostTiles => "most",
+            StrategyKind::Random => "random",
+            StrategyKind::Optimal => "optimal",
+            StrategyKind::OptimalSum => "optimal-sum",
+            StrategyKind::OptimalDigits => "optimal-digits",
+        }
+    }
+
+    /// True if this strategy can play a board of `total` tiles
+    pub fn supports(&self, total: usize) -> bool {
+        match self {
+            StrategyKind::Optimal | StrategyKind::OptimalSum | StrategyKind::OptimalDigits => {
+                total <= MAX_SOLVER_TILES
+            }
+            _ => true,
+        }
+    }
+
+    /// True if this strategy always makes the same move from the same board and roll
+    pub fn deterministic(&self) -> bool {
+        *self != StrategyKind::Random
+    }
+
+    /// Construct a fresh instance of this strategy for a game with these rules
+    pub fn build(&self, rules: &Rules) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Greedy => Box::new(Greedy),
+            StrategyKind::HighestFirst => Box::new(HighestFirst),
+            StrategyKind::FewestTiles => Box::new(FewestTiles),
+            StrategyKind::MostTiles => Box::new(MostTiles),
+            StrategyKind::Random => Box::new(Random::new()),
+            StrategyKind::Optimal => optimal(Objective::WinProbability, rules),
+            StrategyKind::OptimalSum => optimal(Objective::ExpectedSum, rules),
+            StrategyKind::OptimalDigits => optimal(Objective::ExpectedDigits, rules),
+        }
+    }
+
+    /// Cycle to the next strategy in the list
+    pub fn next(&self) -> StrategyKind {
+        let idx = StrategyKind::ALL.iter().position(|x| x == self).unwrap();
+        StrategyKind::ALL[(idx + 1) % StrategyKind::ALL.len()]
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StrategyKind::ALL
+            .iter()
+            .find(|kind| kind.name() == s)
+            .copied()
+            .ok_or_else(|| Error::unknown("strategy", s))
+    }
+}
+
+fn optimal(objective: Objective, rules: &Rules) -> Box<dyn Strategy> {
+    Box::new(Solver::new(rules, objective))
+}
+
+/// Shut the matching tile, else the pair with the lowest small tile, else the first N-way split
+pub struct Greedy;
+
+impl Strategy for Greedy {
+    fn choose_from(&mut self, _open: TileSet, _roll: usize, mut moves: Subsets) -> Option<TileSet> {
+        // Subsets are already listed in greedy preference order
+        moves.next()
+    }
+}
+
+/// Shut the combination containing the highest possible tiles
+pub struct HighestFirst;
+
+impl Strategy for HighestFirst {
+    fn choose_from(&mut self, _open: TileSet, _roll: usize, moves: Subsets) -> Option<TileSet> {
+        // TileSets compare by their highest tiles first
+        moves.max()
+    }
+}
+
+/// Shut as few tiles as possible, preferring high tiles
+pub struct FewestTiles;
+
+impl Strategy for FewestTiles {
+    fn choose_from(&mut self, _open: TileSet, _roll: usize, moves: Subsets) -> Option<TileSet> {
+        moves.min_by_key(|x| (x.len(), Reverse(*x)))
+    }
+}
+
+/// Shut as many tiles as possible, preferring high tiles
+pub struct MostTiles;
+
+impl Strategy for MostTiles {
+    fn choose_from(&mut self, _open: TileSet, _roll: usize, moves: Subsets) -> Option<TileSet> {
+        moves.max_by_key(|x| (x.len(), *x))
+    }
+}
+
+/// Shut any valid combination, chosen uniformly at random
+pub struct Random {
+    rng: StdRng,
+}
+
+impl Random {
+    pub fn new() -> Random {
+        Random {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> Random {
+        Random {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for Random {
+    fn choose_from(&mut self, _open: TileSet, _roll: usize, moves: Subsets) -> Option<TileSet> {
+        let moves: Vec<TileSet> = moves.collect();
+        if moves.is_empty() {
+            return None;
+        }
+        Some(moves[self.rng.gen_range(0, moves.len())])
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::DiceConfig;
+
+    fn set(tiles: &[usize]) -> TileSet {
+        TileSet::from_tiles(tiles)
+    }
+
+    #[test]
+    fn test_greedy() {
+        let open = set(&[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(Greedy.choose(open, 7), Some(set(&[7])));
+        let open = set(&[1, 2, 3, 4, 5, 6, 8, 9]);
+        assert_eq!(Greedy.choose(open, 7), Some(set(&[6, 1])));
+        let open = set(&[1, 2, 3, 5, 9]);
+        assert_eq!(Greedy.choose(open, 11), Some(set(&[9, 2])));
+        let open = set(&[1, 2, 3, 4, 9]);
+        assert_eq!(Greedy.choose(open, 10), Some(set(&[9, 1])));
+        let open = set(&[1, 2, 3, 4]);
+        assert_eq!(Greedy.choose(open, 10), Some(set(&[1, 2, 3, 4])));
+        let open = set(&[1, 2, 4]);
+        assert_eq!(Greedy.choose(open, 8), None);
+    }
+
+    #[test]
+    fn test_default_one_die() {
+        let mut rules = Rules::default();
+        assert!(Greedy.one_die(set(&[1]), &rules));
+        // 12 chances in 36 with one die against 11 with two
+        assert!(Greedy.one_die(set(&[4, 5]), &rules));
+        // Every roll of either is playable, so keep both dice
+        assert!(!Greedy.one_die(set(&[1, 2, 3, 4, 5, 6]), &rules));
+        // Two dice play 35 rolls in 36, but only 14 when one tile may be shut
+        assert!(!Greedy.one_die(set(&[3, 4, 5, 6]), &rules));
+        rules.max_shut = Some(1);
+        assert!(Greedy.one_die(set(&[3, 4, 5, 6]), &rules));
+        // A die loaded to always roll 6 only helps when 6 is open
+        let loaded = Rules {
+            dice: DiceConfig {
+                weights: Some(vec![0.0, 0.0, 0.0, 0.0, 0.0, 1.0]),
+                ..DiceConfig::default()
+            },
+            ..Rules::default()
+        };
+        assert!(Greedy.one_die(set(&[6]), &loaded));
+        assert!(!Greedy.one_die(set(&[5]), &loaded));
+    }
+
+    #[test]
+    fn test_highest_first() {
+        let open = set(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(HighestFirst.choose(open, 9), Some(set(&[3, 6])));
+        assert_eq!(HighestFirst.choose(open, 12), Some(set(&[1, 5, 6])));
+        assert_eq!(HighestFirst.choose(open, 1), Some(set(&[1])));
+        assert_eq!(HighestFirst.choose(set(&[2, 3]), 4), None);
+    }
+
+    #[test]
+    fn test_fewest_tiles() {
+        let open = set(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(FewestTiles.choose(open, 6), Some(set(&[6])));
+        assert_eq!(FewestTiles.choose(open, 9), Some(set(&[3, 6])));
+        assert_eq!(FewestTiles.choose(open, 12), Some(set(&[1, 5, 6])));
+    }
+
+    #[test]
+    fn test_most_tiles() {
+        let open = set(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(MostTiles.choose(open, 6), Some(set(&[1, 2, 3])));
+        assert_eq!(MostTiles.choose(open, 12), Some(set(&[1, 2, 3, 6])));
+    }
+
+    #[test]
+    fn test_random_is_valid() {
+        let open = set(&[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let mut strategy = Random::new();
+        for _ in 0..100 {
+            let tiles = strategy.choose(open, 10).unwrap();
+            assert_eq!(tiles.sum(), 10);
+        }
+        assert_eq!(strategy.choose(set(&[1, 2]), 5), None);
+        let mut strategy1 = Random::with_seed(3);
+        let mut strategy2 = Random::with_seed(3);
+        for _ in 0..100 {
+            assert_eq!(strategy1.choose(open, 10), strategy2.choose(open, 10));
+        }
+    }
+
+    #[test]
+    fn test_strategy_kind_cycle() {
+        let mut kind = StrategyKind::Greedy;
+        for _ in 0..StrategyKind::ALL.len() {
+            kind = kind.next();
+        }
+        assert_eq!(kind, StrategyKind::Greedy);
+        assert!(StrategyKind::Optimal.supports(12));
+        assert!(!StrategyKind::OptimalSum.supports(MAX_SOLVER_TILES + 1));
+        assert!(StrategyKind::Random.supports(32));
+    }
+
+    #[test]
+    fn test_strategy_kind_parse() {
+        for kind in StrategyKind::ALL.iter() {
+            assert_eq!(kind.name().parse::<StrategyKind>().unwrap(), *kind);
+        }
+        assert!("lucky".parse::<StrategyKind>().is_err());
+    }
+}
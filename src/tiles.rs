@@ -0,0 +1,333 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Largest number of tiles a board can hold
+pub const MAX_TILES: usize = 32;
+
+/// Largest roll covered by the precomputed subset table
+const MAX_TABLE_ROLL: usize = 64;
+
+/// TileSet is a set of tiles stored as a bitmask (bit 0 is tile 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TileSet(pub u32);
+
+impl TileSet {
+    pub const EMPTY: TileSet = TileSet(0);
+
+    /// Every tile from 1 to total
+    pub fn full(total: usize) -> TileSet {
+        assert!(
+            total <= MAX_TILES,
+            "Boards hold at most {} tiles",
+            MAX_TILES
+        );
+        TileSet(((1u64 << total) - 1) as u32)
+    }
+
+    /// Single tile, or an empty set if the tile cannot be on a board
+    pub fn single(tile: usize) -> TileSet {
+        if tile == 0 || tile > MAX_TILES {
+            return TileSet::EMPTY;
+        }
+        TileSet(1 << (tile - 1))
+    }
+
+    pub fn from_tiles(tiles: &[usize]) -> TileSet {
+        tiles
+            .iter()
+            .fold(TileSet::EMPTY, |acc, &x| acc.union(TileSet::single(x)))
+    }
+
+    pub fn contains(self, tile: usize) -> bool {
+        !TileSet::single(tile).is_empty() && self.is_superset(TileSet::single(tile))
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_superset(self, other: TileSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn union(self, other: TileSet) -> TileSet {
+        TileSet(self.0 | other.0)
+    }
+
+    pub fn difference(self, other: TileSet) -> TileSet {
+        TileSet(self.0 & !other.0)
+    }
+
+    /// Number of tiles in the set
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Sum of the tile values in the set
+    pub fn sum(self) -> usize {
+        self.iter().sum()
+    }
+
+    /// Largest tile in the set
+    pub fn max(self) -> Option<usize> {
+        match self.0 {
+            0 => None,
+            bits => Some(32 - bits.leading_zeros() as usize),
+        }
+    }
+
+    /// Iterate over the tiles in ascending order
+    pub fn iter(self) -> TileIter {
+        TileIter(self.0)
+    }
+
+    pub fn to_vec(self) -> Vec<usize> {
+        self.iter().collect()
+    }
+}
+
+impl fmt::Display for TileSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tiles: Vec<String> = self.iter().map(|x| x.to_string()).collect();
+        write!(f, "{{{}}}", tiles.join(", "))
+    }
+}
+
+/// Saved as the list of tiles in ascending order
+impl Serialize for TileSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for TileSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TileSet, D::Error> {
+        let tiles = Vec::<usize>::deserialize(deserializer)?;
+        let set = TileSet::from_tiles(&tiles);
+        if set.len() != tiles.len() {
+            return Err(Error::custom(format!(
+                "tiles must be distinct and from 1 to {}",
+                MAX_TILES
+            )));
+        }
+        Ok(set)
+    }
+}
+
+impl IntoIterator for TileSet {
+    type Item = usize;
+    type IntoIter = TileIter;
+
+    fn into_iter(self) -> TileIter {
+        self.iter()
+    }
+}
+
+/// Ascending iterator over the tiles in a TileSet
+pub struct TileIter(u32);
+
+impl Iterator for TileIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let tile = self.0.trailing_zeros() as usize + 1;
+        self.0 &= self.0 - 1;
+        Some(tile)
+    }
+}
+
+fn collect_partitions(
+    target: usize,
+    min: usize,
+    current: &mut Vec<usize>,
+    out: &mut Vec<Vec<usize>>,
+) {
+    for tile in min..=target.min(MAX_TILES) {
+        current.push(tile);
+        if tile == target {
+            out.push(current.clone());
+        } else {
+            collect_partitions(target - tile, tile + 1, current, out);
+        }
+        current.pop();
+    }
+}
+
+/// Every set of distinct tiles that sums to each roll, indexed by roll
+///
+/// Each list is ordered the way the greedy strategy prefers moves: single tiles,
+/// then pairs, then larger splits, ties broken by the smallest tiles first.
+fn subset_table() -> &'static Vec<Vec<TileSet>> {
+    static TABLE: OnceLock<Vec<Vec<TileSet>>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        (0..=MAX_TABLE_ROLL)
+            .map(|roll| {
+                let mut parts = Vec::new();
+                if roll > 0 {
+                    collect_partitions(roll, 1, &mut Vec::new(), &mut parts);
+                }
+                parts.sort_by_key(|x| (x.len().min(3), x.clone()));
+                parts.iter().map(|x| TileSet::from_tiles(x)).collect()
+            })
+            .collect()
+    })
+}
+
+struct VecTotal {
+    values: Vec<usize>,
+    total: usize,
+}
+
+impl VecTotal {
+    fn push(&mut self, val: usize) {
+        self.total += val;
+        self.values.push(val)
+    }
+    fn pop(&mut self) -> usize {
+        match self.values.pop() {
+            Some(val) => {
+                self.total -= val;
+                val
+            }
+            None => 0,
+        }
+    }
+}
+
+fn collect_slice(sumvec: &mut VecTotal, slice: &[usize], target: usize, out: &mut Vec<Vec<usize>>) {
+    for ii in 0..slice.len() {
+        sumvec.push(slice[ii]);
+        match sumvec.total.cmp(&target) {
+            Ordering::Less => collect_slice(sumvec, &slice[ii + 1..], target, out),
+            Ordering::Greater => {}
+            Ordering::Equal => out.push(sumvec.values.clone()),
+        };
+        sumvec.pop();
+    }
+}
+
+/// Return every combination of the (ascending) open tiles that sums to target
+pub fn combinations(open: &[usize], target: usize) -> Vec<Vec<usize>> {
+    let mut sumvec = VecTotal {
+        values: Vec::new(),
+        total: 0,
+    };
+    let mut out = Vec::new();
+    collect_slice(&mut sumvec, open, target, &mut out);
+    out
+}
+
+/// Every subset of `open` that sums to `roll`, in greedy preference order
+pub fn subsets(open: TileSet, roll: usize) -> Subsets {
+    if roll > MAX_TABLE_ROLL {
+        // Rolls this large are rare enough to search the open tiles directly
+        let mut found = combinations(&open.to_vec(), roll);
+        found.sort_by_key(|x| (x.len().min(3), x.clone()));
+        let sets: Vec<TileSet> = found.iter().map(|x| TileSet::from_tiles(x)).collect();
+        return Subsets {
+            open,
+            max_tiles: MAX_TILES,
+            source: SubsetSource::Searched(sets.into_iter()),
+        };
+    }
+    Subsets {
+        open,
+        max_tiles: MAX_TILES,
+        source: SubsetSource::Table(subset_table()[roll].iter()),
+    }
+}
+
+enum SubsetSource {
+    Table(std::slice::Iter<'static, TileSet>),
+    Searched(std::vec::IntoIter<TileSet>),
+}
+
+/// Iterator over the subsets of open tiles that sum to a roll
+pub struct Subsets {
+    open: TileSet,
+    max_tiles: usize,
+    source: SubsetSource,
+}
+
+impl Subsets {
+    /// Skip subsets of more than `max_tiles` tiles
+    pub fn at_most(mut self, max_tiles: usize) -> Subsets {
+        self.max_tiles = max_tiles;
+        self
+    }
+}
+
+impl Iterator for Subsets {
+    type Item = TileSet;
+
+    fn next(&mut self) -> Option<TileSet> {
+        let (open, max_tiles) = (self.open, self.max_tiles);
+        let allowed = |x: &TileSet| open.is_superset(*x) && x.len() <= max_tiles;
+        match &mut self.source {
+            SubsetSource::Table(iter) => iter.copied().find(allowed),
+            SubsetSource::Searched(iter) => iter.find(allowed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tileset_basics() {
+        let set = TileSet::from_tiles(&[1, 3, 12]);
+        assert_eq!(set, TileSet(0b1000_0000_0101));
+        assert_eq!(set.to_vec(), vec![1, 3, 12]);
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.sum(), 16);
+        assert_eq!(set.max(), Some(12));
+        assert!(set.contains(3));
+        assert!(!set.contains(2));
+        assert!(!set.contains(0));
+        assert_eq!(TileSet::full(4).to_vec(), vec![1, 2, 3, 4]);
+        assert_eq!(TileSet::full(32).len(), 32);
+        assert_eq!(TileSet::full(0), TileSet::EMPTY);
+        assert_eq!(set.to_string(), "{1, 3, 12}");
+    }
+
+    #[test]
+    fn test_subsets_order() {
+        let open = TileSet::full(6);
+        let found: Vec<Vec<usize>> = subsets(open, 6).map(|x| x.to_vec()).collect();
+        assert_eq!(found, vec![vec![6], vec![1, 5], vec![2, 4], vec![1, 2, 3]]);
+        let open = TileSet::from_tiles(&[1, 2, 3, 5, 6]);
+        let found: Vec<Vec<usize>> = subsets(open, 12).map(|x| x.to_vec()).collect();
+        assert_eq!(found, vec![vec![1, 2, 3, 6], vec![1, 5, 6]]);
+        assert_eq!(subsets(open, 0).count(), 0);
+        assert_eq!(subsets(TileSet::full(12), 78).count(), 1);
+        // Sums of all 14 tiles except {5}, {1, 4} or {2, 3}
+        let found: Vec<Vec<usize>> = subsets(TileSet::full(14), 100)
+            .map(|x| x.to_vec())
+            .collect();
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0], vec![1, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14]);
+        let found: Vec<Vec<usize>> = subsets(TileSet::full(6), 6)
+            .at_most(2)
+            .map(|x| x.to_vec())
+            .collect();
+        assert_eq!(found, vec![vec![6], vec![1, 5], vec![2, 4]]);
+        assert_eq!(subsets(TileSet::full(14), 100).at_most(12).count(), 2);
+    }
+
+    #[test]
+    fn test_combinations() {
+        let open = vec![1, 2, 3, 4, 5, 6];
+        assert_eq!(
+            combinations(&open, 6),
+            vec![vec![1, 2, 3], vec![1, 5], vec![2, 4], vec![6]]
+        );
+        assert_eq!(combinations(&open, 22), Vec::<Vec<usize>>::new());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::game::{game_seed, simulate_seeded};
+use crate::interval::{normal_cdf, normal_quantile, Interval};
+use crate::rules::Rules;
+use crate::score::ScoreRule;
+use crate::strategy::StrategyKind;
+use rayon::prelude::*;
+
+/// Number of consecutive games each tournament worker plays for every strategy
+const BLOCK: u64 = 1000;
+
+/// Every strategy able to play `tiles` tiles, with `baseline` first
+pub fn lineup(baseline: StrategyKind, tiles: usize) -> Vec<StrategyKind> {
+    let others = StrategyKind::ALL.iter().copied().filter(|&x| x != baseline);
+    Some(baseline)
+        .into_iter()
+        .chain(others)
+        .filter(|x| x.supports(tiles))
+        .collect()
+}
+
+/// Running sums of a per-game difference between two strategies
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PairedDiff {
+    pub games: u64,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl PairedDiff {
+    pub fn add(&mut self, diff: f64) {
+        self.games += 1;
+        self.sum += diff;
+        self.sum_sq += diff * diff;
+    }
+
+    pub fn merge(&mut self, other: &PairedDiff) {
+        self.games += other.games;
+        self.sum += other.sum;
+        self.sum_sq += other.sum_sq;
+    }
+
+    /// Average difference per game
+    pub fn mean(&self) -> f64 {
+        self.sum / self.games.max(1) as f64
+    }
+
+    /// Standard error of the average difference
+    pub fn std_err(&self) -> f64 {
+        if self.games < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.games as f64;
+        let variance = (self.sum_sq - self.sum * self.sum / n) / (n - 1.0);
+        (variance.max(0.0) / n).sqrt()
+    }
+
+    /// Interval for the average difference (normal approximation)
+    pub fn interval(&self, confidence: f64) -> Interval {
+        let spread = normal_quantile(0.5 + confidence / 2.0) * self.std_err();
+        Interval {
+            lo: self.mean() - spread,
+            hi: self.mean() + spread,
+        }
+    }
+
+    /// Two-sided p-value for the strategies doing equally well on average
+    pub fn p_value(&self) -> f64 {
+        let err = self.std_err();
+        if err == 0.0 {
+            // Every game gave the same difference
+            return if self.sum == 0.0 { 1.0 } else { 0.0 };
+        }
+        2.0 * normal_cdf(-(self.mean() / err).abs())
+    }
+}
+
+/// Results of one strategy, compared game by game with the first strategy
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub strategy: StrategyKind,
+    pub won: u64,
+    /// Sum of the game scores under the tournament score rule
+    pub score: u64,
+    /// Won minus won by the baseline, per game
+    pub win_diff: PairedDiff,
+    /// Score minus the baseline's score, per game
+    pub score_diff: PairedDiff,
+}
+
+/// Tournament plays several strategies on the same seeded dice (common random numbers)
+///
+/// Game N of every strategy rolls dice seeded with `game_seed(seed, N)`, so the luck
+/// of the dice mostly cancels out of the paired differences and far fewer games are
+/// needed to tell strategies apart than with independent runs.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub rules: Rules,
+    pub rule: ScoreRule,
+    pub seed: u64,
+    /// Games played by every strategy
+    pub games: u64,
+    /// One standing per strategy, the first being the baseline
+    pub standings: Vec<Standing>,
+}
+
+impl Tournament {
+    pub fn new(
+        strategies: &[StrategyKind],
+        rules: Rules,
+        rule: ScoreRule,
+        seed: u64,
+    ) -> Tournament {
+        let standings = strategies
+            .iter()
+            .map(|&strategy| Standing {
+                strategy,
+                won: 0,
+                score: 0,
+                win_diff: PairedDiff::default(),
+                score_diff: PairedDiff::default(),
+            })
+            .collect();
+        Tournament {
+            rules,
+            rule,
+            seed,
+            games: 0,
+            standings,
+        }
+    }
+
+    /// Play the next `games` games with every strategy on the current rayon pool
+    pub fn play(&mut self, games: u64) {
+        let (start, end) = (self.games, self.games + games);
+        let kinds: Vec<StrategyKind> = self.standings.iter().map(|x| x.strategy).collect();
+        let (rules, rule, seed) = (&self.rules, self.rule, self.seed);
+        let blocks: Vec<Vec<Standing>> = (start / BLOCK..end.div_ceil(BLOCK))
+            .into_par_iter()
+            .map_init(
+                || kinds.iter().map(|x| x.build(rules)).collect::<Vec<_>>(),
+                |strategies, block| {
+                    let mut standings =
+                        Tournament::new(&kinds, rules.clone(), rule, seed).standings;
+                    for ii in start.max(block * BLOCK)..end.min((block + 1) * BLOCK) {
+                        let game_seed = game_seed(seed, ii);
+                        let mut baseline = (0.0, 0.0);
+                        for (jj, strategy) in strategies.iter_mut().enumerate() {
+                            let game = simulate_seeded(strategy.as_mut(), game_seed, rules);
+                            let (won, score) = (game.victory() as u64, game.score(rule));
+                            if jj == 0 {
+                                baseline = (won as f64, score as f64);
+                            }
+                            let standing = &mut standings[jj];
+                            standing.won += won;
+                            standing.score += score;
+                            standing.win_diff.add(won as f64 - baseline.0);
+                            standing.score_diff.add(score as f64 - baseline.1);
+                        }
+                    }
+                    standings
+                },
+            )
+            .collect();
+        for block in blocks.iter() {
+            for (standing, other) in self.standings.iter_mut().zip(block.iter()) {
+                standing.won += other.won;
+                standing.score += other.score;
+                standing.win_diff.merge(&other.win_diff);
+                standing.score_diff.merge(&other.score_diff);
+            }
+        }
+        self.games = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::simulate_batch;
+
+    #[test]
+    fn test_paired_diff() {
+        let mut diff = PairedDiff::default();
+        for &x in [1.0, 0.0, 1.0, 0.0].iter() {
+            diff.add(x);
+        }
+        assert_eq!(diff.mean(), 0.5);
+        assert!((diff.std_err() - (1.0f64 / 12.0).sqrt()).abs() < 1e-12);
+        let interval = diff.interval(0.95);
+        assert!(interval.contains(0.5) && interval.lo < 0.0);
+        let mut same = PairedDiff::default();
+        same.add(0.0);
+        same.add(0.0);
+        assert_eq!(same.p_value(), 1.0);
+    }
+
+    #[test]
+    fn test_lineup() {
+        let strategies = lineup(StrategyKind::Random, 12);
+        assert_eq!(strategies.len(), StrategyKind::ALL.len());
+        assert_eq!(strategies[0], StrategyKind::Random);
+        assert!(!lineup(StrategyKind::Greedy, 20).contains(&StrategyKind::Optimal));
+    }
+
+    #[test]
+    fn test_tournament() {
+        let rules = Rules::new(9);
+        let kinds = [
+            StrategyKind::Greedy,
+            StrategyKind::Greedy,
+            StrategyKind::Optimal,
+        ];
+        let mut tournament = Tournament::new(&kinds, rules.clone(), ScoreRule::Sum, 11);
+        tournament.play(1500);
+        tournament.play(1500);
+        assert_eq!(tournament.games, 3000);
+        // Each strategy plays the same games as a batch with the same seed
+        let batch = simulate_batch(3000, 11, StrategyKind::Greedy, &rules);
+        let greedy = &tournament.standings[0];
+        assert_eq!(greedy.won, batch.num_won);
+        assert_eq!(
+            greedy.win_diff,
+            PairedDiff {
+                games: 3000,
+                sum: 0.0,
+                sum_sq: 0.0
+            }
+        );
+        // Identical strategies on common dice never differ
+        assert_eq!(tournament.standings[1].score_diff.p_value(), 1.0);
+        let optimal = &tournament.standings[2];
+        assert_eq!(optimal.win_diff.games, 3000);
+        assert!(optimal.win_diff.mean() > -0.01);
+    }
+}
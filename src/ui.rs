@@ -1,4 +1,7 @@
 use crate::app::{App, AppState};
+use shutthebox::interval::{IntervalMethod, CONFIDENCE};
+use shutthebox::tiles::TileSet;
+use shutthebox::tournament::PairedDiff;
 use std::cmp;
 use tui::{
     backend::Backend,
@@ -6,11 +9,7 @@ use tui::{
     style::{Color, Modifier, Style},
     symbols,
     text::{Span, Spans},
-    widgets::canvas::{Canvas, Line, Map, MapResolution, Rectangle},
-    widgets::{
-        Axis, BarChart, Block, Borders, Chart, Dataset, Gauge, List, ListItem, ListState,
-        Paragraph, Row, Sparkline, Table, Tabs, Wrap,
-    },
+    widgets::{Axis, Block, Borders, Chart, Dataset, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
@@ -26,16 +25,35 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         .constraints([Constraint::Length(30), Constraint::Min(0)].as_ref())
         .split(chunks[1]);
     {
+        let hints = app.hint.is_some() && app.state == AppState::ManualGame;
+        let constraints = if app.hotseat.is_some() || hints {
+            vec![
+                Constraint::Length(5),
+                Constraint::Length(6),
+                Constraint::Min(0),
+            ]
+        } else {
+            vec![Constraint::Length(5), Constraint::Min(0)]
+        };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(5), Constraint::Min(0)].as_ref())
+            .constraints(constraints)
             .split(chunks[0]);
         {
             draw_dice(f, chunks[0], app);
             draw_text(f, chunks[1], app);
+            if app.hotseat.is_some() {
+                draw_scoreboard(f, chunks[2], app);
+            } else if hints {
+                draw_hints(f, chunks[2], app);
+            }
         }
     }
-    draw_stats(f, chunks[1], app);
+    if app.state == AppState::Compare {
+        draw_tournament(f, chunks[1], app);
+    } else {
+        draw_stats(f, chunks[1], app);
+    }
 }
 
 fn draw_boxes<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
@@ -44,8 +62,13 @@ fn draw_boxes<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
         .borders(Borders::ALL);
     f.render_widget(block, area);
 
-    let nboxes = 12;
-    let constraints = vec![Constraint::Ratio(1, nboxes); nboxes as usize];
+    let hint = app.hint_best().unwrap_or(TileSet::EMPTY);
+    // A reviewed move is drawn on the board it was played from
+    let reviewed = app
+        .review_move()
+        .map(|(x, _)| (x.before, x.chosen.unwrap_or(TileSet::EMPTY), x.best));
+    let nboxes = app.game.total;
+    let constraints = vec![Constraint::Ratio(1, nboxes as u32); nboxes];
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .margin(1)
@@ -64,14 +87,30 @@ fn draw_boxes<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
             .style(Style::default().fg(Color::White))
             .alignment(Alignment::Center)
             .wrap(Wrap { trim: true });
-        if app.state != AppState::Idle {
+        if let Some((before, chosen, best)) = reviewed {
+            let mut style = Style::default();
+            if !before.contains(ii + 1) {
+                style = style.fg(Color::White).bg(Color::Blue);
+            } else if best.is_some_and(|x| x.contains(ii + 1)) {
+                // Box is part of the best move
+                style = style.bg(Color::Green);
+            }
+            if chosen.contains(ii + 1) {
+                // Box was shut by the move played
+                style = style.fg(Color::Red);
+            }
+            paragraph = paragraph.style(style)
+        } else if app.state != AppState::Idle {
             let mut style = Style::default();
             if app.game.get_status(ii + 1).unwrap() {
                 // Box is shut
                 style = style.fg(Color::White).bg(Color::Blue);
+            } else if hint.contains(ii + 1) {
+                // Box is part of the recommended move
+                style = style.bg(Color::Green);
             }
             let selected = ii == app.selection && app.tasks.state.selected().is_none();
-            let staged = app.staging.iter().any(|&x| x == ii);
+            let staged = app.staging.contains(&ii);
             if selected && app.state == AppState::ManualGame {
                 // Box is SELECTED
                 style = if staged {
@@ -107,6 +146,37 @@ fn draw_dice<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
                 Style::default().fg(Color::Red),
             )),
         ],
+        AppState::Review => match app.review_move() {
+            Some((reviewed, solver)) => vec![
+                Spans::from(Span::styled(
+                    format!("ROLL = {}", reviewed.roll),
+                    Style::default().fg(Color::Red),
+                )),
+                Spans::from(format!("Move {}/{}", app.review_idx + 1, app.review.len())),
+                Spans::from(Span::styled(
+                    if reviewed.is_blunder(solver) {
+                        "BLUNDER"
+                    } else {
+                        ""
+                    },
+                    Style::default().fg(Color::Red),
+                )),
+            ],
+            None => vec![Spans::from("No moves to review")],
+        },
+        AppState::Replay => {
+            let moves = app.replay.as_ref().map_or(&[][..], |x| &x.moves[..]);
+            let (dice, roll) = match moves.get(app.replay_step) {
+                Some(played) => (app.dice.pprint(), format!("ROLL = {}", played.sum)),
+                None if app.game.victory() => (String::new(), "WON".to_string()),
+                None => (String::new(), "LOST".to_string()),
+            };
+            vec![
+                Spans::from(Span::styled(dice, Style::default().fg(Color::Red))),
+                Spans::from(Span::styled(roll, Style::default().fg(Color::Red))),
+                Spans::from(format!("Move {}/{}", app.replay_step, moves.len())),
+            ]
+        }
         _ => vec![
             Spans::from(Span::styled("⚀ ⚁ ⚂ ⚃ ⚄ ⚅", Style::default().fg(Color::Red))),
             Spans::from(Span::styled("ROLL", Style::default().fg(Color::Red))),
@@ -132,9 +202,17 @@ fn draw_text<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
         .map(|i| ListItem::new(vec![Spans::from(Span::raw(*i))]))
         .collect();
     let title = match app.state {
-        AppState::ManualGame => "Playing!",
+        AppState::ManualGame if app.choosing_dice => "How Many Dice?",
+        AppState::ManualGame if app.hotseat.is_some() => "Hot-Seat!",
+        AppState::ManualGame => "Playing! (U: Undo, Y: Redo)",
+        AppState::Review => "Review (Left/Right)",
+        AppState::Replay => "Replay (Left/Right)",
         _ => "Select Game",
     };
+    let title = match &app.rejected {
+        Some(err) => format!("Illegal Move: {}", err),
+        None => title.to_string(),
+    };
     let tasks = List::new(tasks)
         .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
@@ -142,6 +220,169 @@ fn draw_text<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
     f.render_stateful_widget(tasks, area, &mut app.tasks.state);
 }
 
+fn draw_hints<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
+    let objective = match app.hint {
+        Some(objective) => objective,
+        None => return,
+    };
+    let moves = app.hint_moves();
+    let mut text: Vec<Spans> = moves
+        .iter()
+        .enumerate()
+        .map(|(ii, (tiles, value))| {
+            let value = if objective.maximize() {
+                format!("{:.3}%", 100.0 * value)
+            } else if *value >= 1e5 {
+                // Digit scores grow too long for the pane
+                format!("{:.3e}", value)
+            } else {
+                format!("{:.2}", value)
+            };
+            let style = if ii == 0 {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default()
+            };
+            Spans::from(Span::styled(
+                format!("{:<16} {:>10}", tiles.to_string(), value),
+                style,
+            ))
+        })
+        .collect();
+    if text.is_empty() {
+        text.push(Spans::from("No move to rate"));
+    }
+    let title = format!("Hint: {} (Press H)", objective.name());
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+fn draw_scoreboard<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
+    let hotseat = match &app.hotseat {
+        Some(hotseat) => hotseat,
+        None => return,
+    };
+    let mut text = vec![Spans::from(Span::styled(
+        "  Player        Total Round",
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    for (ii, player) in hotseat.players.iter().enumerate() {
+        let playing = !hotseat.round_over() && ii == hotseat.current();
+        let winner = hotseat
+            .result
+            .as_ref()
+            .is_some_and(|x| x.winners.contains(&ii));
+        let round = match player.round_score {
+            Some(score) => score.to_string(),
+            None => "-".to_string(),
+        };
+        let mut style = Style::default();
+        if playing {
+            style = style.fg(Color::Red);
+        } else if winner {
+            style = style.fg(Color::Green);
+        }
+        text.push(Spans::from(Span::styled(
+            format!(
+                "{}{:<12.12} {:>6} {:>5}",
+                if playing { "> " } else { "  " },
+                player.name,
+                player.total,
+                round
+            ),
+            style,
+        )));
+    }
+    if let Some(result) = &hotseat.result {
+        let names: Vec<&str> = result
+            .winners
+            .iter()
+            .map(|&ii| hotseat.players[ii].name.as_str())
+            .collect();
+        let verb = if result.shut {
+            "shut the box!"
+        } else {
+            "wins!"
+        };
+        text.push(Spans::from(""));
+        text.push(Spans::from(Span::styled(
+            format!("{} {}", names.join(" & "), verb),
+            Style::default().fg(Color::Green),
+        )));
+    }
+    let title = format!("Round {} ({} scoring)", hotseat.round, hotseat.rule.name());
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+fn draw_tournament<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
+    let tournament = match &app.tournament {
+        Some(tournament) => tournament,
+        None => return,
+    };
+    let games = tournament.games.max(1) as f64;
+    let mut text = vec![Spans::from(Span::styled(
+        format!(
+            "{:<15} {:>7} {:>17} {:>7}  {:>7} {:>17} {:>7}",
+            "Strategy",
+            "Win %",
+            "Difference",
+            "p",
+            tournament.rule.name(),
+            "Difference",
+            "p"
+        ),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    for (ii, standing) in tournament.standings.iter().enumerate() {
+        // Color significant differences by whether they beat the baseline
+        let diff = |diff: &PairedDiff, scale: f64, higher: bool| {
+            if ii == 0 {
+                return Span::raw(format!("{:>17} {:>7}", "baseline", "-"));
+            }
+            let mut style = Style::default();
+            if diff.p_value() < 1.0 - CONFIDENCE {
+                let better = (diff.mean() > 0.0) == higher;
+                style = style.fg(if better { Color::Green } else { Color::Red });
+            }
+            Span::styled(
+                format!(
+                    "{:>+8.3} +/- {:<5.3} {:>7.4}",
+                    scale * diff.mean(),
+                    scale * diff.interval(CONFIDENCE).half_width(),
+                    diff.p_value()
+                ),
+                style,
+            )
+        };
+        text.push(Spans::from(vec![
+            Span::raw(format!(
+                "{:<15} {:>7.3} ",
+                standing.strategy.name(),
+                100.0 * standing.won as f64 / games
+            )),
+            diff(&standing.win_diff, 100.0, true),
+            Span::raw(format!("  {:>7.3} ", standing.score as f64 / games)),
+            diff(&standing.score_diff, 1.0, false),
+        ]));
+    }
+    let title = format!(
+        "Compare Strategies: {} games each, same dice (seed {})",
+        tournament.games, tournament.seed
+    );
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
 fn draw_stats<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -161,14 +402,145 @@ fn draw_stats<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
             format!("Total: {:?}", stats.num_total),
             Style::default(),
         ));
-        let display = vec![span_rolls, span_wins, span_total];
+        let optimal = match app.optimal {
+            Some(optimal) => format!("{:.3}%", 100.0 * optimal),
+            None => "n/a".to_string(),
+        };
+        let span_score = Spans::from(Span::styled(
+            format!(
+                "Average Score: {:.2} ({}, Press R to Switch)",
+                stats.score_histogram(app.score_rule).mean(),
+                app.score_rule.name()
+            ),
+            Style::default(),
+        ));
+        let exact = match &app.exact {
+            Some(exact) => format!("{:.3}%", 100.0 * exact.win),
+            None => "n/a".to_string(),
+        };
+        let span_rate = Spans::from(Span::styled(
+            format!(
+                "Win Rate: {:.3}% (Exact {}, Optimal {})",
+                100.0 * stats.num_won as f64 / cmp::max(stats.num_total, 1) as f64,
+                exact,
+                optimal
+            ),
+            Style::default(),
+        ));
+        let span_interval = Spans::from(Span::styled(
+            format!(
+                "Win Rate {:.0}% Interval: +/- {:.4}%",
+                100.0 * CONFIDENCE,
+                100.0 * stats.win_interval(IntervalMethod::Wilson).half_width()
+            ),
+            Style::default(),
+        ));
+        let span_board = Spans::from(Span::styled(
+            format!("Board: {} tiles (Press B to Switch)", app.rules.tiles),
+            Style::default(),
+        ));
+        let span_one_die = Spans::from(Span::styled(
+            format!("One Die: {} (Press O to Switch)", app.rules.one_die.name()),
+            Style::default(),
+        ));
+        let span_max_shut = Spans::from(Span::styled(
+            format!(
+                "Max Tiles Per Roll: {} (Press M to Switch)",
+                app.rules
+                    .max_shut
+                    .map_or("any".to_string(), |x| x.to_string())
+            ),
+            Style::default(),
+        ));
+        let span_dice = Spans::from(Span::styled(
+            format!("Dice: {} (Press D to Switch)", app.rules.dice),
+            Style::default(),
+        ));
+        let span_strategy = Spans::from(Span::styled(
+            format!("Strategy: {} (Press S to Switch)", app.strategy.name()),
+            Style::default(),
+        ));
+        let span_players = Spans::from(Span::styled(
+            format!(
+                "Hot-Seat Players: {} (Press +/- to Change)",
+                app.players.len()
+            ),
+            Style::default(),
+        ));
+        let span_replay = Spans::from(Span::styled(
+            match &app.status {
+                Some(status) => format!("Replay: {}", status),
+                None => "Replay: Press W to Save".to_string(),
+            },
+            Style::default(),
+        ));
+        let display = vec![
+            span_rolls,
+            span_wins,
+            span_total,
+            span_score,
+            span_rate,
+            span_interval,
+            span_strategy,
+            span_board,
+            span_one_die,
+            span_max_shut,
+            span_dice,
+            span_players,
+            span_replay,
+        ];
         let paragraph = Paragraph::new(display)
             .block(Block::default().title("Stats").borders(Borders::ALL))
             .alignment(Alignment::Left)
             .wrap(Wrap { trim: true });
         f.render_widget(paragraph, chunks[0]);
     }
-    draw_plots(f, chunks[1], app);
+    if app.state == AppState::Review {
+        draw_review(f, chunks[1], app);
+    } else {
+        draw_plots(f, chunks[1], app);
+    }
+}
+
+fn draw_review<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
+    let solver = match app.review_move() {
+        Some((_, solver)) => solver,
+        None => return,
+    };
+    let mut text = vec![Spans::from(Span::styled(
+        "  #  Roll  Played           P(win)  Best             P(win)",
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    let name = |x: Option<TileSet>| x.map_or("-".to_string(), |x| x.to_string());
+    for (ii, reviewed) in app.review.iter().enumerate() {
+        let blunder = reviewed.is_blunder(solver);
+        let mut style = Style::default();
+        if blunder {
+            style = style.fg(Color::Red);
+        }
+        if ii == app.review_idx {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        text.push(Spans::from(Span::styled(
+            format!(
+                "{}{:>2} {:>5}  {:<16} {:>6.2}%  {:<16} {:>6.2}% {}",
+                if ii == app.review_idx { ">" } else { " " },
+                ii + 1,
+                reviewed.roll,
+                name(reviewed.chosen),
+                100.0 * reviewed.chosen_value,
+                name(reviewed.best),
+                100.0 * reviewed.best_value,
+                if blunder { "BLUNDER" } else { "" }
+            ),
+            style,
+        )));
+    }
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().title("Move Review").borders(Borders::ALL))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
 }
 
 fn draw_plots<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
@@ -181,15 +553,27 @@ fn draw_plots<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
     }
 }
 
+/// Expected counts for `games` games from exact per-game probabilities, plotted like the counts
+fn expected_counts(probs: Option<&Vec<f64>>, games: u64) -> Vec<(f64, f64)> {
+    probs.map_or(Vec::new(), |probs| {
+        probs
+            .iter()
+            .enumerate()
+            .map(|(ii, prob)| (ii as f64, prob * games as f64))
+            .collect()
+    })
+}
+
 fn create_chart<'a>(
-    data: &'a Vec<(f64, f64)>,
+    data: &'a [(f64, f64)],
+    exact: &'a [(f64, f64)],
     title: &'a str,
     xaxis: &'a str,
     yaxis: &'a str,
 ) -> Chart<'a> {
     let xmax = data.iter().map(|&x| x.0 as u64).max().unwrap() as f64;
     let xhalf = (xmax + 1.0) / 2.0;
-    let ymax = data.iter().map(|&x| x.1 as u64).max().unwrap() as f64;
+    let ymax = data.iter().chain(exact).map(|&x| x.1 as u64).max().unwrap() as f64;
     let nextpow10 = cmp::max(10, 10_u64.pow(ymax.log10().ceil() as u32)) as f64;
     let x_labels = vec![
         Span::raw(format!("{}", 1)),
@@ -210,11 +594,21 @@ fn create_chart<'a>(
             Style::default().add_modifier(Modifier::BOLD),
         ),
     ];
-    let datasets = vec![Dataset::default()
+    let mut datasets = vec![Dataset::default()
         .name("Count")
         .marker(symbols::Marker::Dot)
         .style(Style::default().fg(Color::Cyan))
         .data(data)];
+    if !exact.is_empty() {
+        // Overlay the exact expectation to show whether the counts have converged
+        datasets.push(
+            Dataset::default()
+                .name("Exact")
+                .marker(symbols::Marker::Braille)
+                .style(Style::default().fg(Color::Yellow))
+                .data(exact),
+        );
+    }
     Chart::new(datasets)
         .block(
             Block::default()
@@ -239,17 +633,20 @@ fn create_chart<'a>(
 
 fn draw_hist_nrolls<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
     let mut result: Vec<(f64, f64)> = Vec::new();
+    let exact;
     {
         let stats = app.stats.lock().unwrap();
         for (ii, &total) in stats.count_nrolls.iter().enumerate() {
             result.push((ii as f64, total as f64));
         }
+        exact = expected_counts(app.exact.as_ref().map(|x| &x.nrolls), stats.num_total);
     }
 
     let block = Block::default().title("Plots").borders(Borders::ALL);
     f.render_widget(block, area);
     let chart = create_chart(
         &result,
+        &exact,
         "Number of Rolls Per Game (Press P to Switch)",
         "Rolls",
         "Count",
@@ -259,16 +656,19 @@ fn draw_hist_nrolls<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
 
 fn draw_hist_rawrolls<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
     let mut result: Vec<(f64, f64)> = Vec::new();
+    let exact;
     {
         let stats = app.stats.lock().unwrap();
         for (ii, &total) in stats.count_rawrolls.iter().enumerate() {
             result.push((ii as f64, total as f64));
         }
+        exact = expected_counts(app.exact.as_ref().map(|x| &x.rawrolls), stats.num_total);
     }
     let block = Block::default().title("Plots").borders(Borders::ALL);
     f.render_widget(block, area);
     let chart = create_chart(
         &result,
+        &exact,
         "Dice Roll Count (Press P to Switch)",
         "Rolls",
         "Count",
@@ -278,31 +678,47 @@ fn draw_hist_rawrolls<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
 
 fn draw_hist_nshut<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
     let mut result: Vec<(f64, f64)> = Vec::new();
+    let exact;
     {
         let stats = app.stats.lock().unwrap();
         for (ii, &total) in stats.count_shut.iter().enumerate() {
             result.push((ii as f64, total as f64));
         }
+        exact = expected_counts(app.exact.as_ref().map(|x| &x.shut), stats.num_total);
     }
 
     let block = Block::default().title("Plots").borders(Borders::ALL);
     f.render_widget(block, area);
-    let chart = create_chart(&result, "Boxes Shut (Press P to Switch)", "Box", "Count");
+    let chart = create_chart(
+        &result,
+        &exact,
+        "Boxes Shut (Press P to Switch)",
+        "Box",
+        "Count",
+    );
     f.render_widget(chart, area);
 }
 
 fn draw_hist_lastroll<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
     let mut result: Vec<(f64, f64)> = Vec::new();
+    let exact;
     {
         let stats = app.stats.lock().unwrap();
         for (ii, &total) in stats.count_lastroll.iter().enumerate() {
             result.push((ii as f64, total as f64));
         }
+        exact = expected_counts(app.exact.as_ref().map(|x| &x.lastroll), stats.num_total);
     }
 
     let block = Block::default().title("Plots").borders(Borders::ALL);
     f.render_widget(block, area);
-    let chart = create_chart(&result, "Losing Roll (Press P to Switch)", "Rolls", "Count");
+    let chart = create_chart(
+        &result,
+        &exact,
+        "Losing Roll (Press P to Switch)",
+        "Rolls",
+        "Count",
+    );
     f.render_widget(chart, area);
 }
 
@@ -313,23 +729,32 @@ fn arange(min: u64, max: u64, step: u64) -> impl Iterator<Item = (u64, u64)> {
 }
 
 fn draw_hist_wins<B: Backend>(f: &mut Frame<B>, area: Rect, app: &mut App) {
-    let mut sum = 0;
     let mut result: Vec<(f64, f64)> = Vec::new();
+    let exact;
     {
         let stats = app.stats.lock().unwrap();
         for bounds in arange(0, 2000, 25) {
             let count = stats
                 .games_between_win
                 .count_between(bounds.0, bounds.1 - 1);
-            sum += count;
             result.push((bounds.1 as f64, count as f64));
         }
+        exact = match &app.exact {
+            Some(outcomes) => arange(0, 2000, 25)
+                .map(|(lo, hi)| {
+                    let expected = outcomes.win_gap(lo, hi - 1) * stats.num_won as f64;
+                    (hi as f64, expected)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
     }
 
     let block = Block::default().title("Plots").borders(Borders::ALL);
     f.render_widget(block, area);
     let chart = create_chart(
         &result,
+        &exact,
         "Games Between Wins (Press P to Switch)",
         "Games",
         "Count",
@@ -0,0 +1,22 @@
+use shutthebox::replay::Replay;
+use shutthebox::{simulate_game, Dice, Error, Rules, Statistics, StrategyKind};
+
+#[test]
+fn test_library_api() {
+    let rules = Rules::new(9);
+    let mut strategy = StrategyKind::Greedy.build(&rules);
+    let mut dice = Dice::with_seed(3);
+    let mut stats = Statistics::new(rules.tiles);
+    for _ in 0..100 {
+        let game = simulate_game(strategy.as_mut(), &mut dice, &rules);
+        stats.save_game(&game);
+    }
+    assert_eq!(stats.num_total, 100);
+
+    assert!(matches!(
+        "lucky".parse::<StrategyKind>(),
+        Err(Error::UnknownName { .. })
+    ));
+    assert!(matches!(Rules::new(0).validate(), Err(Error::Tiles(0))));
+    assert!(matches!(Replay::from_json("{"), Err(Error::Json(_))));
+}